@@ -1,4 +1,6 @@
 use regex::Regex;
+
+use crate::aoc_params::{ParamSpec, Params};
 use std::{
     env,
     fmt::{Debug, Display},
@@ -10,36 +12,44 @@ use std::{
 };
 
 pub fn read_file(relative_to: &Path, name: &str) -> String {
-    let dir: &Path;
-    if relative_to.is_file() {
-        dir = relative_to.parent().unwrap();
+    let dir = if relative_to.is_file() {
+        relative_to.parent().unwrap()
     } else {
-        dir = relative_to;
-    }
+        relative_to
+    };
 
     let input_file_path = dir.join(name);
-    let contents = fs::read_to_string(input_file_path).expect("Input file should be there");
-    return contents;
+    fs::read_to_string(input_file_path).expect("Input file should be there")
 }
 
 pub type AocResult = Result<i64, String>;
 
+/// A day of the puzzle. `solve_a` and `solve_b` run the parameterised solvers with the
+/// default params.
 pub trait AocTask {
-    fn solve_a(&self, contents: String) -> AocResult;
-    fn solve_b(&self, contents: String) -> AocResult;
+    fn solve_a(&self, contents: String) -> AocResult {
+        self.solve_a_with_params(contents, &Params::defaults(&self.params(&TaskPart::A))?)
+    }
+    fn solve_b(&self, contents: String) -> AocResult {
+        self.solve_b_with_params(contents, &Params::defaults(&self.params(&TaskPart::B))?)
+    }
+
+    /// Parameters of `part`, overridable with `--param <name>=<value>` or a config file.
+    fn params(&self, _part: &TaskPart) -> Vec<ParamSpec> {
+        Vec::new()
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult;
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum TaskPart {
+    #[default]
     A,
     B,
 }
 
-impl Default for TaskPart {
-    fn default() -> Self {
-        return TaskPart::A;
-    }
-}
 impl Display for TaskPart {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let val = match self {
@@ -71,38 +81,129 @@ struct AocArgs {
     day_number: usize,
     part: TaskPart,
     input: String,
+    param_overrides: Vec<(String, String)>,
+}
+
+/// Run settings read from a file, so that a run can be reproduced:
+///
+/// ```text
+/// day = 14
+/// part = b
+/// input = input
+///
+/// [params]
+/// grid = 101x103
+/// ```
+#[derive(Debug, Default)]
+struct RunConfig {
+    day: Option<String>,
+    part: Option<String>,
+    input: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl RunConfig {
+    fn read(path: &Path) -> Result<RunConfig, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read config {}: {e}", path.display()))?;
+        contents.parse()
+    }
+}
+
+impl FromStr for RunConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = RunConfig::default();
+        let mut in_params = false;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+            if line.starts_with("[") {
+                in_params = match line {
+                    "[params]" => true,
+                    _ => return Err(format!("line {}: unknown section {line}", i + 1)),
+                };
+                continue;
+            }
+            let (key, value) =
+                parse_assignment(line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            if in_params {
+                config.params.push((key, value));
+                continue;
+            }
+            match key.as_str() {
+                "day" => config.day = Some(value),
+                "part" => config.part = Some(value),
+                "input" => config.input = Some(value),
+                _ => return Err(format!("line {}: unknown key {key}", i + 1)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_assignment(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once("=")
+        .ok_or(format!("expected <name>=<value>, got {s}"))?;
+    Ok((key.trim().to_owned(), value.trim().to_owned()))
 }
 
 static DAY_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:day(\d+)|(\d+))").unwrap());
 
 impl AocArgs {
-    fn parse_day_arg(s: &String) -> Result<usize, String> {
-        let captures = DAY_PATTERN
-            .captures(s.as_str())
-            .ok_or("invalid argument for day")?;
+    fn parse_day_arg(s: &str) -> Result<usize, String> {
+        let captures = DAY_PATTERN.captures(s).ok_or("invalid argument for day")?;
 
         let day_raw = captures.get(1).or(captures.get(2)).unwrap();
         day_raw.as_str().parse::<usize>().map_err(|e| e.to_string())
     }
 
     fn parse_args(n_days_implemented: usize) -> Result<AocArgs, String> {
-        let args: Vec<String> = env::args().skip(1).collect();
+        let mut args: Vec<String> = Vec::new();
+        let mut config = RunConfig::default();
+        let mut cli_overrides = Vec::new();
+        let mut raw_args = env::args().skip(1);
+        while let Some(arg) = raw_args.next() {
+            match arg.as_str() {
+                "--param" | "-p" => {
+                    let raw = raw_args.next().ok_or("missing value for --param")?;
+                    cli_overrides.push(parse_assignment(&raw)?);
+                }
+                "--config" | "-c" => {
+                    let path = raw_args.next().ok_or("missing value for --config")?;
+                    config = RunConfig::read(Path::new(&path))?;
+                }
+                _ => match arg.strip_prefix("--") {
+                    // `--<name>` switches on a flag parameter
+                    Some(flag) => cli_overrides.push((flag.replace("-", "_"), "true".to_owned())),
+                    None => args.push(arg),
+                },
+            }
+        }
 
         let day_number = args
             .get(ARG_INDEX_DAY)
+            .or(config.day.as_ref())
+            .map(String::as_str)
             .ok_or(String::from("missing day parameter"))
             .and_then(Self::parse_day_arg)?;
-        if day_number - 1 >= n_days_implemented {
+        if day_number == 0 || day_number > n_days_implemented {
             return Err(format!("day {} is not yet implemented", day_number));
         }
 
         let task_part = args
             .get(ARG_INDEX_PART)
+            .or(config.part.as_ref())
             .map(|r| r.parse::<TaskPart>())
             .unwrap_or(Ok(TaskPart::default()))?;
 
         let input_type = args
             .get(ARG_INDEX_INPUT)
+            .or(config.input.as_ref())
             .map(String::as_str)
             .unwrap_or("sample");
         let input_file = match input_type.to_lowercase().as_str() {
@@ -111,28 +212,34 @@ impl AocArgs {
             _ => input_type,
         }
         .to_owned();
+
+        let mut param_overrides = config.params;
+        param_overrides.extend(cli_overrides);
         Ok(AocArgs {
             day_number,
             part: task_part,
             input: input_file,
+            param_overrides,
         })
     }
 
     fn help() {
-        println!("cargo run <day> [part] [input]")
+        println!(
+            "cargo run <day> [part] [input] [--param <name>=<value>]... [--<flag>]... [--config <file>]"
+        )
     }
 }
 
 pub fn run<D: IntoIterator<Item = Box<dyn AocTask>>>(days: D) {
     let days = days.into_iter().collect::<Vec<_>>();
-    let args = AocArgs::parse_args(days.len());
-    if args.is_err() {
-        println!("{}", args.unwrap_err());
-        AocArgs::help();
-        panic!()
-    }
-
-    let args = args.unwrap();
+    let args = match AocArgs::parse_args(days.len()) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("{e}");
+            AocArgs::help();
+            panic!()
+        }
+    };
 
     println!(
         "Evaluating day {} / {} for input {}",
@@ -140,6 +247,22 @@ pub fn run<D: IntoIterator<Item = Box<dyn AocTask>>>(days: D) {
     );
     let day = days.get(args.day_number - 1).unwrap();
 
+    let params = match Params::resolve(
+        &day.params(&args.part),
+        Some(&args.input),
+        &args.param_overrides,
+    ) {
+        Ok(params) => params,
+        Err(e) => {
+            println!("{}", e);
+            AocArgs::help();
+            panic!()
+        }
+    };
+    if !params.is_empty() {
+        println!("Parameters: {}", params);
+    }
+
     let contents = read_file(
         Path::new(file!())
             .parent()
@@ -150,8 +273,8 @@ pub fn run<D: IntoIterator<Item = Box<dyn AocTask>>>(days: D) {
     );
 
     let solver: Box<dyn Fn(String) -> AocResult> = match args.part {
-        TaskPart::A => Box::new(|c| day.solve_a(c)),
-        TaskPart::B => Box::new(|c| day.solve_b(c)),
+        TaskPart::A => Box::new(|c| day.solve_a_with_params(c, &params)),
+        TaskPart::B => Box::new(|c| day.solve_b_with_params(c, &params)),
     };
 
     let now = Instant::now();
@@ -169,3 +292,25 @@ pub fn run<D: IntoIterator<Item = Box<dyn AocTask>>>(days: D) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_run_config() -> Result<(), String> {
+        let config: RunConfig = "# reproduces the answer of part b\nday = 14\npart = b\n\n[params]\ngrid = 101x103\nseconds=100\n".parse()?;
+
+        assert_eq!(config.day.as_deref(), Some("14"));
+        assert_eq!(config.part.as_deref(), Some("b"));
+        assert_eq!(config.input, None);
+        assert_eq!(
+            config.params,
+            vec![
+                ("grid".to_owned(), "101x103".to_owned()),
+                ("seconds".to_owned(), "100".to_owned())
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::BTreeMap, fmt::Display, ops::Range};
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParamKind {
+    UInt,
+    /// `<width>x<height>`
+    Dimensions,
+    /// Comma separated unsigned integers
+    List,
+    /// `<start>..<end>`, end exclusive
+    Range,
+    Text,
+    /// `true`/`false`, given as `--<name>` on the command line
+    Flag,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamValue {
+    UInt(u64),
+    Dimensions(usize, usize),
+    List(Vec<u64>),
+    Range(u64, u64),
+    Text(String),
+    Flag(bool),
+}
+
+impl ParamKind {
+    fn parse(&self, raw: &str) -> Result<ParamValue, String> {
+        let raw = raw.trim();
+        Ok(match self {
+            Self::UInt => ParamValue::UInt(raw.parse().map_err(|e| format!("{raw}: {e}"))?),
+            Self::Dimensions => {
+                let (width, height) = raw
+                    .split_once("x")
+                    .ok_or(format!("expected <width>x<height>, got {raw}"))?;
+                ParamValue::Dimensions(
+                    width.trim().parse().map_err(|e| format!("{raw}: {e}"))?,
+                    height.trim().parse().map_err(|e| format!("{raw}: {e}"))?,
+                )
+            }
+            Self::List => ParamValue::List(
+                raw.split(",")
+                    .map(|v| v.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("{raw}: {e}"))?,
+            ),
+            Self::Range => {
+                let (start, end) = raw
+                    .split_once("..")
+                    .ok_or(format!("expected <start>..<end>, got {raw}"))?;
+                ParamValue::Range(
+                    start.trim().parse().map_err(|e| format!("{raw}: {e}"))?,
+                    end.trim().parse().map_err(|e| format!("{raw}: {e}"))?,
+                )
+            }
+            Self::Text => ParamValue::Text(raw.to_owned()),
+            Self::Flag => ParamValue::Flag(match raw.to_lowercase().as_str() {
+                "true" | "yes" | "1" => true,
+                "false" | "no" | "0" => false,
+                _ => return Err(format!("expected true or false, got {raw}")),
+            }),
+        })
+    }
+}
+
+impl Display for ParamValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UInt(v) => write!(f, "{v}"),
+            Self::Dimensions(width, height) => write!(f, "{width}x{height}"),
+            Self::List(values) => write!(f, "{}", values.iter().join(",")),
+            Self::Range(start, end) => write!(f, "{start}..{end}"),
+            Self::Text(v) => f.write_str(v),
+            Self::Flag(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+/// Declares a named parameter of a day, with a default that may differ per input file.
+pub struct ParamSpec {
+    name: &'static str,
+    kind: ParamKind,
+    default: &'static str,
+    input_defaults: Vec<(&'static str, &'static str)>,
+}
+
+impl ParamSpec {
+    pub fn new(name: &'static str, kind: ParamKind, default: &'static str) -> Self {
+        ParamSpec {
+            name,
+            kind,
+            default,
+            input_defaults: Vec::new(),
+        }
+    }
+
+    pub fn with_input_default(mut self, input: &'static str, default: &'static str) -> Self {
+        self.input_defaults.push((input, default));
+        self
+    }
+
+    fn default_for(&self, input: Option<&str>) -> &'static str {
+        input
+            .and_then(|input| self.input_defaults.iter().find(|(i, _)| *i == input))
+            .map(|&(_, default)| default)
+            .unwrap_or(self.default)
+    }
+}
+
+/// Resolved parameter values of a run.
+#[derive(Debug)]
+pub struct Params {
+    values: BTreeMap<&'static str, ParamValue>,
+}
+
+impl Params {
+    pub fn defaults(specs: &[ParamSpec]) -> Result<Params, String> {
+        Self::resolve(specs, None, &[])
+    }
+
+    /// Starts from the defaults for `input` and applies `overrides` in order, so later
+    /// overrides win. Every override must name a declared parameter.
+    pub fn resolve(
+        specs: &[ParamSpec],
+        input: Option<&str>,
+        overrides: &[(String, String)],
+    ) -> Result<Params, String> {
+        let mut values = BTreeMap::new();
+        for spec in specs {
+            let value = spec
+                .kind
+                .parse(spec.default_for(input))
+                .map_err(|e| format!("invalid default for parameter {}: {e}", spec.name))?;
+            values.insert(spec.name, value);
+        }
+        for (name, raw) in overrides {
+            let spec = specs.iter().find(|s| s.name == name).ok_or(format!(
+                "unknown parameter {name}, expected one of: {}",
+                specs.iter().map(|s| s.name).join(", ")
+            ))?;
+            let value = spec
+                .kind
+                .parse(raw)
+                .map_err(|e| format!("invalid value for parameter {name}: {e}"))?;
+            values.insert(spec.name, value);
+        }
+        Ok(Params { values })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn get(&self, name: &str) -> Result<&ParamValue, String> {
+        self.values
+            .get(name)
+            .ok_or(format!("parameter {name} is not declared"))
+    }
+
+    pub fn uint(&self, name: &str) -> Result<u64, String> {
+        match self.get(name)? {
+            ParamValue::UInt(v) => Ok(*v),
+            v => Err(format!(
+                "parameter {name} is not an unsigned integer: {v:?}"
+            )),
+        }
+    }
+
+    pub fn dimensions(&self, name: &str) -> Result<(usize, usize), String> {
+        match self.get(name)? {
+            ParamValue::Dimensions(width, height) => Ok((*width, *height)),
+            v => Err(format!("parameter {name} is not a dimension: {v:?}")),
+        }
+    }
+
+    pub fn list(&self, name: &str) -> Result<&[u64], String> {
+        match self.get(name)? {
+            ParamValue::List(values) => Ok(values),
+            v => Err(format!("parameter {name} is not a list: {v:?}")),
+        }
+    }
+
+    pub fn range(&self, name: &str) -> Result<Range<u64>, String> {
+        match self.get(name)? {
+            ParamValue::Range(start, end) => Ok(*start..*end),
+            v => Err(format!("parameter {name} is not a range: {v:?}")),
+        }
+    }
+
+    pub fn text(&self, name: &str) -> Result<&str, String> {
+        match self.get(name)? {
+            ParamValue::Text(v) => Ok(v),
+            v => Err(format!("parameter {name} is not a text: {v:?}")),
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> Result<bool, String> {
+        match self.get(name)? {
+            ParamValue::Flag(v) => Ok(*v),
+            v => Err(format!("parameter {name} is not a flag: {v:?}")),
+        }
+    }
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let formatted = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .join(" ");
+        f.write_str(&formatted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs() -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("blinks", ParamKind::UInt, "25"),
+            ParamSpec::new("grid", ParamKind::Dimensions, "101x103")
+                .with_input_default("sample.txt", "11x7"),
+        ]
+    }
+
+    #[test]
+    fn test_input_defaults() -> Result<(), String> {
+        let params = Params::resolve(&specs(), Some("sample.txt"), &[])?;
+
+        assert_eq!(params.dimensions("grid")?, (11, 7));
+        assert_eq!(params.uint("blinks")?, 25);
+        assert_eq!(Params::defaults(&specs())?.dimensions("grid")?, (101, 103));
+        Ok(())
+    }
+
+    #[test]
+    fn test_overrides() -> Result<(), String> {
+        let overrides = vec![
+            ("blinks".to_owned(), "40".to_owned()),
+            ("blinks".to_owned(), "41".to_owned()),
+        ];
+        let params = Params::resolve(&specs(), Some("sample.txt"), &overrides)?;
+
+        assert_eq!(params.uint("blinks")?, 41);
+        assert_eq!(params.to_string(), "blinks=41 grid=11x7");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_invalid() {
+        let unknown = vec![("blink".to_owned(), "40".to_owned())];
+        assert!(Params::resolve(&specs(), None, &unknown).is_err());
+
+        let invalid = vec![("grid".to_owned(), "11,7".to_owned())];
+        assert!(Params::resolve(&specs(), None, &invalid).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use std::ops::Rem;
+
+/// Greatest common divisor. With negative numbers the sign of the result follows the
+/// inputs, callers take the absolute value where it matters.
+pub fn gcd<T>(a: T, b: T) -> T
+where
+    T: Copy + PartialEq + Default + Rem<Output = T>,
+{
+    if b == T::default() {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Deterministic xorshift generator for randomized tests. The seed must not be 0.
+#[cfg(test)]
+pub struct Random(u64);
+
+#[cfg(test)]
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Next number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12usize, 18), 6);
+        assert_eq!(gcd(7usize, 0), 7);
+        assert_eq!(gcd(0usize, 0), 0);
+        assert_eq!(gcd(-12isize, 18).abs(), 6);
+        assert_eq!(gcd(1i128 << 100, 1 << 64), 1 << 64);
+    }
+}
@@ -1,15 +1,18 @@
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask},
+    aoc_params::Params,
+};
 
 use std::{collections::HashMap, iter::zip};
 
 pub struct Day01;
 
 impl AocTask for Day01 {
-    fn solve_a(&self, contents: String) -> AocResult {
+    fn solve_a_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let mut left = Vec::new();
         let mut right = Vec::new();
         for line in contents.lines() {
-            let values: Vec<&str> = line.trim().split_whitespace().collect();
+            let values: Vec<&str> = line.split_whitespace().collect();
             left.push(values[0].parse::<i32>().map_err(|e| e.to_string())?);
             right.push(values[1].parse::<i32>().map_err(|e| e.to_string())?);
         }
@@ -26,11 +29,11 @@ impl AocTask for Day01 {
         Ok(total_dist as i64)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
+    fn solve_b_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let mut left: Vec<i32> = Vec::new();
         let mut right: Vec<i32> = Vec::new();
         for line in contents.lines() {
-            let values: Vec<&str> = line.trim().split_whitespace().collect();
+            let values: Vec<&str> = line.split_whitespace().collect();
             left.push(values[0].parse::<i32>().map_err(|e| e.to_string())?);
             right.push(values[1].parse::<i32>().map_err(|e| e.to_string())?);
         }
@@ -0,0 +1,214 @@
+use std::{fmt::Display, ops::Range};
+
+/// What makes two adjacent levels unsafe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// The levels go the other way than the first pair did
+    DirectionChange,
+    ZeroDelta,
+    /// The levels differ by more or less than allowed
+    DeltaOutOfRange(i32),
+}
+
+/// First unsafe pair of levels, `index` being the one of the second level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Failure {
+    pub index: usize,
+    pub problem: Problem,
+}
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (a, b) = (self.index - 1, self.index);
+        match self.problem {
+            Problem::DirectionChange => write!(f, "direction changes at levels {a} and {b}"),
+            Problem::ZeroDelta => write!(f, "levels {a} and {b} are equal"),
+            Problem::DeltaOutOfRange(delta) => {
+                write!(f, "levels {a} and {b} differ by {}", delta.abs())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Safe,
+    /// Safe once the levels at `removed` are taken out
+    Dampened {
+        removed: Vec<usize>,
+        failure: Failure,
+    },
+    Unsafe {
+        failure: Failure,
+    },
+}
+impl Verdict {
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, Self::Unsafe { .. })
+    }
+}
+
+/// Allowed absolute difference between adjacent levels and how many levels the dampener may
+/// remove.
+pub struct Rules {
+    pub deltas: Range<i32>,
+    pub tolerance: usize,
+}
+
+impl Rules {
+    fn check(&self, value: i32, next_value: i32, increasing: bool) -> Option<Problem> {
+        let delta = next_value - value;
+        if !self.deltas.contains(&delta.abs()) {
+            Some(match delta {
+                0 => Problem::ZeroDelta,
+                _ => Problem::DeltaOutOfRange(delta),
+            })
+        } else if delta != 0 && (delta > 0) != increasing {
+            Some(Problem::DirectionChange)
+        } else {
+            None
+        }
+    }
+
+    /// The direction is set by the first pair of levels.
+    pub fn first_failure(&self, report: &[i32]) -> Option<Failure> {
+        let increasing = report.get(1)? > &report[0];
+        report.windows(2).enumerate().find_map(|(i, pair)| {
+            let problem = self.check(pair[0], pair[1], increasing)?;
+            Some(Failure {
+                index: i + 1,
+                problem,
+            })
+        })
+    }
+
+    pub fn analyse(&self, report: &[i32]) -> Verdict {
+        let Some(failure) = self.first_failure(report) else {
+            return Verdict::Safe;
+        };
+        [true, false]
+            .into_iter()
+            .filter_map(|increasing| self.fewest_removals(report, increasing))
+            .filter(|removed| removed.len() <= self.tolerance)
+            .min_by_key(|removed| removed.len())
+            .map_or(Verdict::Unsafe { failure }, |removed| Verdict::Dampened {
+                removed,
+                failure,
+            })
+    }
+
+    /// Smallest set of levels to remove so the rest goes in one direction. `removals[i]` is
+    /// the fewest removals before level `i` for a safe sequence ending in it. A kept level
+    /// can only follow one of the `tolerance + 1` levels before it, which makes this
+    /// O(n * tolerance). Ties keep the closest previous level, which removes the same levels
+    /// as the puzzle's examples.
+    fn fewest_removals(&self, report: &[i32], increasing: bool) -> Option<Vec<usize>> {
+        let n = report.len();
+        let mut removals = vec![0; n];
+        let mut previous = vec![None; n];
+        for i in 0..n {
+            removals[i] = i;
+            for p in (i.saturating_sub(self.tolerance + 1)..i).rev() {
+                let count = removals[p] + i - p - 1;
+                if count < removals[i] && self.check(report[p], report[i], increasing).is_none() {
+                    removals[i] = count;
+                    previous[i] = Some(p);
+                }
+            }
+        }
+        let last = (0..n).rev().min_by_key(|&i| removals[i] + n - 1 - i)?;
+        if removals[last] + n - 1 - last > self.tolerance {
+            return None;
+        }
+        let mut kept = vec![false; n];
+        let mut i = Some(last);
+        while let Some(k) = i {
+            kept[k] = true;
+            i = previous[k];
+        }
+        Some((0..n).filter(|&i| !kept[i]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc_util::Random;
+
+    use super::*;
+
+    fn brute_force(rules: &Rules, report: &[i32], removed: usize) -> bool {
+        if rules.first_failure(report).is_none() {
+            return true;
+        }
+        removed < rules.tolerance
+            && (0..report.len()).any(|skip| {
+                let mut rest = report.to_vec();
+                rest.remove(skip);
+                brute_force(rules, &rest, removed + 1)
+            })
+    }
+
+    #[test]
+    fn test_dampened_explanation() {
+        let rules = Rules {
+            deltas: 1..4,
+            tolerance: 1,
+        };
+
+        assert_eq!(
+            rules.analyse(&[1, 3, 2, 4, 5]),
+            Verdict::Dampened {
+                removed: vec![1],
+                failure: Failure {
+                    index: 2,
+                    problem: Problem::DirectionChange
+                }
+            }
+        );
+        let Verdict::Unsafe { failure } = rules.analyse(&[1, 2, 7, 8, 9]) else {
+            panic!("1 2 7 8 9 is unsafe");
+        };
+        assert_eq!(failure.to_string(), "levels 1 and 2 differ by 5");
+        assert_eq!(
+            rules.analyse(&[8, 6, 4, 4, 1]),
+            Verdict::Dampened {
+                removed: vec![2],
+                failure: Failure {
+                    index: 3,
+                    problem: Problem::ZeroDelta
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut random = Random::new(7);
+        for tolerance in 0..3 {
+            let rules = Rules {
+                deltas: 1..4,
+                tolerance,
+            };
+            for _ in 0..2000 {
+                let len = random.below(8);
+                let report = (0..len)
+                    .map(|_| random.below(10) as i32)
+                    .collect::<Vec<_>>();
+
+                let verdict = rules.analyse(&report);
+
+                assert_eq!(
+                    verdict.is_safe(),
+                    brute_force(&rules, &report, 0),
+                    "{report:?}"
+                );
+                if let Verdict::Dampened { removed, .. } = verdict {
+                    let rest = (0..len)
+                        .filter(|i| !removed.contains(i))
+                        .map(|i| report[i])
+                        .collect::<Vec<_>>();
+                    assert_eq!(rules.first_failure(&rest), None, "{report:?}");
+                }
+            }
+        }
+    }
+}
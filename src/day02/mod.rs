@@ -1,53 +1,79 @@
-use crate::aoc_core::{AocResult, AocTask};
+use analysis::{Rules, Verdict};
 use itertools::Itertools;
+
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod analysis;
+
 pub struct Day02;
 
 impl AocTask for Day02 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let reports = parse_input(contents)?;
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let tolerance = match part {
+            TaskPart::A => "0",
+            TaskPart::B => "1",
+        };
+        vec![
+            // number of levels the problem dampener may remove from a report
+            ParamSpec::new("tolerance", ParamKind::UInt, tolerance),
+            // allowed absolute difference between adjacent levels, end exclusive
+            ParamSpec::new("deltas", ParamKind::Range, "1..4"),
+            // prints why every report is safe or not
+            ParamSpec::new("explain", ParamKind::Flag, "false"),
+        ]
+    }
 
-        let mut safe_reports = 0;
-        for report in reports {
-            if is_report_safe(report.iter()) {
-                safe_reports += 1;
-            }
-        }
-        Ok(safe_reports as i64)
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_safe_reports(contents, params)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let reports = parse_input(contents)?;
-        let mut safe_reports = 0;
-        for report in reports {
-            for skip_index in 0..report.len() {
-                let iter = report
-                    .iter()
-                    .enumerate()
-                    .filter(|(i, _)| *i != skip_index)
-                    .map(|(_, v)| v);
-                if is_report_safe(iter) {
-                    safe_reports += 1;
-                    break;
-                }
-            }
-        }
-        Ok(safe_reports as i64)
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_safe_reports(contents, params)
     }
 }
 
-fn is_report_safe<'a, T: Iterator<Item = &'a i32>>(report: T) -> bool {
-    let mut increasing = None;
-    for (value, next_value) in report.tuple_windows() {
-        if increasing.is_none() {
-            increasing = Some(next_value > value)
+fn count_safe_reports(contents: String, params: &Params) -> AocResult {
+    let reports = parse_input(contents)?;
+    let deltas = params.range("deltas")?;
+    let rules = Rules {
+        deltas: i32::try_from(deltas.start).map_err(|e| e.to_string())?
+            ..i32::try_from(deltas.end).map_err(|e| e.to_string())?,
+        tolerance: params.uint("tolerance")? as usize,
+    };
+    let explain = params.flag("explain")?;
+
+    let mut safe_reports = 0;
+    for report in reports {
+        let verdict = rules.analyse(&report);
+        if explain {
+            println!(
+                "{}: {}",
+                report.iter().join(" "),
+                describe(&verdict, &report)
+            );
         }
-        let delta = next_value - value;
-        let abs_delta = delta.abs();
-        if abs_delta == 0 || abs_delta > 3 || (delta > 0) != increasing.unwrap() {
-            return false;
+        if verdict.is_safe() {
+            safe_reports += 1;
         }
     }
-    true
+    Ok(safe_reports)
+}
+
+fn describe(verdict: &Verdict, report: &[i32]) -> String {
+    match verdict {
+        Verdict::Safe => String::from("safe"),
+        Verdict::Dampened { removed, failure } => format!(
+            "safe without {} as {failure}",
+            removed
+                .iter()
+                .map(|&i| format!("level {i} ({})", report[i]))
+                .join(", ")
+        ),
+        Verdict::Unsafe { failure } => format!("unsafe as {failure}"),
+    }
 }
 
 fn parse_input(contents: String) -> Result<Vec<Vec<i32>>, String> {
@@ -57,7 +83,7 @@ fn parse_input(contents: String) -> Result<Vec<Vec<i32>>, String> {
             .split_whitespace()
             .map(|v| v.parse::<i32>())
             .partition(Result::is_ok);
-        if errors.len() > 0 {
+        if !errors.is_empty() {
             return Err(String::from("error parsing numbers"));
         }
         let values: Vec<_> = values.into_iter().map(Result::unwrap).collect();
@@ -0,0 +1,111 @@
+//! Differential fuzzing of the regex based `Day03` against `Day03Manual`.
+
+use crate::{aoc_core::AocTask, aoc_util::Random};
+
+use super::{manual::Day03Manual, Day03};
+
+/// Valid instructions and tokens that only almost are one
+const FRAGMENTS: &[&str] = &[
+    "mul(1,2)",
+    "mul(123,456)",
+    "mul(0,999)",
+    "do()",
+    "don't()",
+    "mul(1234,5)",
+    "mul(5,1234)",
+    "mul (1,2)",
+    "mul( 1,2)",
+    "mul(1,2 )",
+    "mul(1,2",
+    "mul[1,2]",
+    "mul(,2)",
+    "mul(1,)",
+    "mul(1,2,3)",
+    "mul(-1,2)",
+    "mulmul(3,4)",
+    "don't(",
+    "don't( )",
+    "do(",
+    "do_not_mul(5,5)",
+    "undo()",
+    "don'tmul(2,2)",
+    "dodon't()",
+];
+
+/// Chars most likely to form or break tokens when mixed into fragments
+const NOISE: &[u8] = b"mul(),don't0123456789 x[]%_'";
+
+fn corrupted_memory(random: &mut Random) -> String {
+    let mut memory = String::new();
+    for _ in 0..random.below(40) {
+        match random.below(3) {
+            0 => memory.push_str(FRAGMENTS[random.below(FRAGMENTS.len())]),
+            _ => memory.push(NOISE[random.below(NOISE.len())] as char),
+        }
+    }
+    memory
+}
+
+/// Results of both implementations if they differ for either part
+fn disagreement(memory: &str) -> Option<String> {
+    let solve = |task: &dyn AocTask| {
+        (
+            task.solve_a(memory.to_owned()),
+            task.solve_b(memory.to_owned()),
+        )
+    };
+    let (regex, manual) = (solve(&Day03), solve(&Day03Manual));
+    (regex != manual).then(|| format!("regex: {regex:?}, manual: {manual:?}"))
+}
+
+/// Removes ever smaller chunks of `input` as long as it keeps `failing`, down to single
+/// chars, so no char can be removed in the end.
+fn shrink(input: &str, failing: impl Fn(&str) -> bool) -> String {
+    let mut chars = input.chars().collect::<Vec<_>>();
+    let mut chunk = chars.len().div_ceil(2).max(1);
+    loop {
+        let mut start = 0;
+        let mut removed_any = false;
+        while start < chars.len() {
+            let end = (start + chunk).min(chars.len());
+            let candidate = chars[..start]
+                .iter()
+                .chain(&chars[end..])
+                .collect::<String>();
+            if failing(&candidate) {
+                chars.drain(start..end);
+                removed_any = true;
+            } else {
+                start += chunk;
+            }
+        }
+        if chunk == 1 && !removed_any {
+            return chars.into_iter().collect();
+        }
+        chunk = (chunk / 2).max(1);
+    }
+}
+
+#[test]
+fn test_shrink() {
+    let shrunk = shrink("a mul(1,2) b don't() c", |s| {
+        s.contains("mul(") && s.contains("'")
+    });
+
+    assert_eq!(shrunk, "mul('");
+}
+
+#[test]
+fn test_implementations_agree() {
+    let mut random = Random::new(2024);
+    for _ in 0..3000 {
+        let memory = corrupted_memory(&mut random);
+        if disagreement(&memory).is_some() {
+            let reproducer = shrink(&memory, |s| disagreement(s).is_some());
+            panic!(
+                "implementations disagree on {reproducer:?}, {}",
+                disagreement(&reproducer).unwrap()
+            );
+        }
+    }
+}
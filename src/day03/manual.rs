@@ -1,89 +1,177 @@
-use std::str::Chars;
+use std::fmt::Display;
 
-use crate::aoc_core::AocTask;
-use itertools::PeekingNext;
+use crate::{
+    aoc_core::{AocResult, AocTask},
+    aoc_params::Params,
+};
 
 pub struct Day03Manual;
 
-struct Scanner<'a> {
-    iter: Chars<'a>,
-    tokens: &'a mut Vec<(i32, i32)>,
+/// Instructions of the corrupted memory language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+/// Name and number of arguments of every instruction. Names that are a prefix of another
+/// one are fine, every name is tried at every position.
+const INSTRUCTIONS: &[(&str, usize)] = &[("mul", 2), ("do", 0), ("don't", 0)];
+
+/// Arguments have one to three digits
+const MAX_DIGITS: usize = 3;
+
+impl Instruction {
+    fn new(name: &str, args: &[i64]) -> Option<Instruction> {
+        match (name, args) {
+            ("mul", &[a, b]) => Some(Self::Mul(a, b)),
+            ("do", []) => Some(Self::Do),
+            ("don't", []) => Some(Self::Dont),
+            _ => None,
+        }
+    }
+}
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mul(a, b) => write!(f, "mul({a},{b})"),
+            Self::Do => write!(f, "do()"),
+            Self::Dont => write!(f, "don't()"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    /// Byte offset of the first char of the instruction in the source
+    pub offset: usize,
+    /// Length in bytes
+    pub len: usize,
+}
+
+/// Finds the well-formed instructions in corrupted memory, skipping everything in between.
+/// Works on bytes, all chars of the language are ASCII.
+pub struct Scanner<'a> {
+    source: &'a [u8],
+    position: usize,
 }
 
 impl Scanner<'_> {
-    fn new<'a>(source: &'a String, tokens: &'a mut Vec<(i32, i32)>) -> Scanner<'a> {
+    pub fn new(source: &str) -> Scanner<'_> {
         Scanner {
-            iter: source.chars(),
-            tokens,
+            source: source.as_bytes(),
+            position: 0,
         }
     }
 
-    #[inline]
-    fn peeking_next(&mut self, expected: char) -> Option<()> {
-        self.iter.peeking_next(|c| *c == expected)?;
-        Some(())
+    fn peeking_next(&self, position: &mut usize, expected: u8) -> Option<()> {
+        (self.source.get(*position) == Some(&expected)).then(|| *position += 1)
     }
 
-    fn consume_digits(&mut self) -> Option<String> {
-        let mut digits = String::from("");
-        loop {
-            let digit = self.iter.peeking_next(char::is_ascii_digit);
-            match digit {
-                Some(d) => {
-                    digits.push(d);
-                }
-                None => {
-                    break;
-                }
-            }
+    fn consume_number(&self, position: &mut usize) -> Option<i64> {
+        let digits = self.source[*position..]
+            .iter()
+            .take(MAX_DIGITS + 1)
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits == 0 || digits > MAX_DIGITS {
+            return None;
         }
-        if digits.is_empty() {
+        let number = self.source[*position..*position + digits]
+            .iter()
+            .fold(0, |n, d| n * 10 + i64::from(d - b'0'));
+        *position += digits;
+        Some(number)
+    }
+
+    /// `name(arg,...)` with exactly `arity` arguments, returning the position after it
+    fn consume_call(&self, start: usize, name: &str, arity: usize) -> Option<(Vec<i64>, usize)> {
+        if !self.source[start..].starts_with(name.as_bytes()) {
             return None;
         }
-        return Some(digits);
-    }
-
-    fn token_mul(&mut self) -> Option<()> {
-        self.peeking_next('(')?;
-        let f1 = self.consume_digits()?;
-        self.peeking_next(',')?;
-        let f2 = self.consume_digits()?;
-        self.peeking_next(')')?;
-        self.tokens
-            .push((f1.parse::<i32>().unwrap(), f2.parse::<i32>().unwrap()));
-        Some(())
-    }
-
-    fn scan(&mut self) -> &Vec<(i32, i32)> {
-        loop {
-            match self.iter.next() {
-                Some(c) => {
-                    if c == 'm'
-                        && self.iter.peeking_next(|c| *c == 'u').is_some()
-                        && self.iter.peeking_next(|c| *c == 'l').is_some()
-                    {
-                        self.token_mul();
-                    }
-                }
-                None => {
-                    break;
-                }
+        let mut position = start + name.len();
+        self.peeking_next(&mut position, b'(')?;
+        let mut args = Vec::with_capacity(arity);
+        for i in 0..arity {
+            if i > 0 {
+                self.peeking_next(&mut position, b',')?;
             }
+            args.push(self.consume_number(&mut position)?);
         }
-        &self.tokens
+        self.peeking_next(&mut position, b')')?;
+        Some((args, position))
+    }
+
+    fn token_at(&self, start: usize) -> Option<Token> {
+        INSTRUCTIONS.iter().find_map(|&(name, arity)| {
+            let (args, end) = self.consume_call(start, name, arity)?;
+            Some(Token {
+                instruction: Instruction::new(name, &args)?,
+                offset: start,
+                len: end - start,
+            })
+        })
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.source.len() {
+            if let Some(token) = self.token_at(self.position) {
+                self.position += token.len;
+                return Some(token);
+            }
+            self.position += 1;
+        }
+        None
+    }
+}
+
+/// Runs instructions one after another. Without `conditionals`, `do()` and `don't()` are
+/// ignored like in part A.
+pub struct Interpreter {
+    conditionals: bool,
+    enabled: bool,
+    total: i64,
+}
+
+impl Interpreter {
+    pub fn new(conditionals: bool) -> Interpreter {
+        Interpreter {
+            conditionals,
+            enabled: true,
+            total: 0,
+        }
+    }
+
+    pub fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Mul(a, b) if self.enabled => self.total += a * b,
+            Instruction::Mul(..) => (),
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = !self.conditionals,
+        }
+    }
+
+    pub fn run(mut self, tokens: impl IntoIterator<Item = Token>) -> i64 {
+        for token in tokens {
+            self.execute(token.instruction);
+        }
+        self.total
     }
 }
 
 impl AocTask for Day03Manual {
-    fn solve_a(&self, contents: String) -> crate::aoc_core::AocResult {
-        let mut tokens = Vec::new();
-        let mut scanner = Scanner::new(&contents, &mut tokens);
-        scanner.scan();
-        Ok(tokens.iter().map(|(x, y)| (*x as i64) * (*y as i64)).sum())
+    fn solve_a_with_params(&self, contents: String, _params: &Params) -> AocResult {
+        Ok(Interpreter::new(false).run(Scanner::new(&contents)))
     }
 
-    fn solve_b(&self, _contents: String) -> crate::aoc_core::AocResult {
-        todo!()
+    fn solve_b_with_params(&self, contents: String, _params: &Params) -> AocResult {
+        Ok(Interpreter::new(true).run(Scanner::new(&contents)))
     }
 }
 
@@ -91,7 +179,7 @@ impl AocTask for Day03Manual {
 mod tests {
     use std::path::Path;
 
-    use crate::aoc_core::read_file;
+    use crate::{aoc_core::read_file, day03::Day03};
 
     use super::*;
 
@@ -99,17 +187,48 @@ mod tests {
     fn test_solve_a_sample() -> Result<(), String> {
         let contents = read_file(Path::new(file!()), "sample.txt");
 
-        Day03Manual.solve_a(contents)?;
-        assert!(false);
+        assert_eq!(Day03Manual.solve_a(contents)?, 161);
         Ok(())
     }
 
     #[test]
-    fn test_solve_a_input() -> Result<(), String> {
+    fn test_solve_b_sample() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample_b.txt");
+
+        assert_eq!(Day03Manual.solve_b(contents)?, 48);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_input() -> Result<(), String> {
         let contents = read_file(Path::new(file!()), "input.txt");
 
-        Day03Manual.solve_a(contents)?;
-        assert!(false);
+        assert_eq!(
+            Day03Manual.solve_a(contents.clone())?,
+            Day03.solve_a(contents.clone())?
+        );
+        assert_eq!(
+            Day03Manual.solve_b(contents.clone())?,
+            Day03.solve_b(contents)?
+        );
         Ok(())
     }
+
+    #[test]
+    fn test_token_offsets() {
+        let tokens = Scanner::new("xdon't()mul(1,22)mul(1234,5)mul(4,5)").collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (token.offset, token.instruction.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, String::from("don't()")),
+                (8, String::from("mul(1,22)")),
+                (28, String::from("mul(4,5)"))
+            ]
+        );
+        assert_eq!(tokens[1].len, 9);
+    }
 }
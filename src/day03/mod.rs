@@ -1,42 +1,55 @@
+#[cfg(test)]
+mod fuzz;
 mod manual;
 
+use lazy_static::lazy_static;
+use manual::Day03Manual;
 use regex::{Captures, Regex};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
 
 pub struct Day03;
 
+lazy_static! {
+    static ref MUL_PATTERN: Regex = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
+    static ref INSTRUCTION_PATTERN: Regex =
+        Regex::new(r"(?:don't\(\)|do\(\)|mul\((\d{1,3}),(\d{1,3})\))").unwrap();
+}
+
 fn parse_factors(captures: Captures<'_>) -> (i64, i64) {
     let factor1 = captures.get(1).unwrap().as_str().parse::<i64>().unwrap();
     let factor2 = captures.get(2).unwrap().as_str().parse::<i64>().unwrap();
-    return (factor1, factor2);
+    (factor1, factor2)
+}
+
+/// Implementation picked with the `implementation` param
+fn implementation(params: &Params) -> Result<Box<dyn AocTask>, String> {
+    match params.text("implementation")? {
+        "regex" => Ok(Box::new(Day03)),
+        "manual" => Ok(Box::new(Day03Manual)),
+        other => Err(format!(
+            "unknown implementation {other}, expected regex or manual"
+        )),
+    }
 }
 
 impl AocTask for Day03 {
     fn solve_a(&self, contents: String) -> AocResult {
-        let re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
         let mut total: i64 = 0;
-        for capture in re.captures_iter(&contents) {
+        for capture in MUL_PATTERN.captures_iter(&contents) {
             let (factor1, factor2) = parse_factors(capture);
             total += factor1 * factor2;
         }
-        Ok(total as i64)
+        Ok(total)
     }
 
     fn solve_b(&self, contents: String) -> AocResult {
-        let do_pattern = r"do\(\)";
-        let dont_pattern = r"don't\(\)";
-        let mul_pattern = r"mul\((\d{1,3}),(\d{1,3})\)";
-
-        let re = Regex::new(&format!(
-            "(?:{}|{}|{})",
-            dont_pattern, do_pattern, mul_pattern
-        ))
-        .unwrap();
-
         let mut total: i64 = 0;
         let mut mul_enabled = true;
-        for capture in re.captures_iter(&contents) {
+        for capture in INSTRUCTION_PATTERN.captures_iter(&contents) {
             let m = capture.get(0).unwrap().as_str();
             match m {
                 "do()" => {
@@ -54,6 +67,21 @@ impl AocTask for Day03 {
                 }
             }
         }
-        Ok(total as i64)
+        Ok(total)
+    }
+
+    fn params(&self, _part: &TaskPart) -> Vec<ParamSpec> {
+        vec![
+            // regex or manual, the hand-written tokenizer and interpreter
+            ParamSpec::new("implementation", ParamKind::Text, "regex"),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        implementation(params)?.solve_a(contents)
+    }
+
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        implementation(params)?.solve_b(contents)
     }
 }
@@ -1,77 +1,72 @@
-use itertools::Itertools;
+use std::str::FromStr;
 
-use crate::aoc_core::{AocResult, AocTask};
+use search::{find_template, find_words, Grid, Template};
+
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod search;
 
 pub struct Day04;
 
-#[inline]
-fn mat_get(lines: &Vec<Vec<char>>, i: i32, j: i32) -> Option<&char> {
-    if i < 0 || j < 0 {
-        return None;
+impl AocTask for Day04 {
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let mut params = vec![
+            // prints every match
+            ParamSpec::new("print", ParamKind::Flag, "false"),
+        ];
+        match part {
+            // comma-separated words to search for in all 8 directions
+            TaskPart::A => params.push(ParamSpec::new("words", ParamKind::Text, "XMAS")),
+            // rows separated by `/`, `.` matching anything, in any rotation or reflection
+            TaskPart::B => params.push(ParamSpec::new("template", ParamKind::Text, "M.S/.A./M.S")),
+        }
+        params
     }
-    return lines.get(i as usize).and_then(|line| line.get(j as usize));
-}
 
-impl AocTask for Day04 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let lines = contents
-            .lines()
-            .map(|line| line.chars().collect_vec())
-            .collect_vec();
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let grid = Grid::from_str(&contents)?;
+        let words = params.text("words")?.split(',').collect::<Vec<_>>();
 
-        let mut occurances: i32 = 0;
-        let width = lines.get(0).unwrap().len() as i32;
-        for i in 0..(lines.len() as i32) {
-            for j in 0..width {
-                'dir_scan: for (di, dj) in [(0, 1), (1, 0), (1, 1), (-1, 1)] {
-                    let mut word = String::from("");
-                    for offset in 0..4 {
-                        match mat_get(&lines, i + (di * offset), j + (dj * offset)) {
-                            Some(c) => {
-                                word.push(*c);
-                            }
-                            None => {
-                                continue 'dir_scan;
-                            }
-                        }
-                    }
-                    if word == "XMAS" || word == "SAMX" {
-                        occurances += 1;
-                    }
-                }
+        let matches = find_words(&grid, &words);
+        if params.flag("print")? {
+            for word_match in &matches {
+                println!("{word_match}");
             }
         }
-        Ok(occurances as i64)
+        Ok(matches.len() as i64)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let lines = contents
-            .lines()
-            .map(|line| line.chars().collect_vec())
-            .collect_vec();
-
-        let mut occurances: i32 = 0;
-        let width = lines.get(0).unwrap().len();
-        for i in 1..(lines.len() - 1) {
-            for j in 1..(width - 1) {
-                let a = lines[i][j];
-                if a != 'A' {
-                    continue;
-                }
-                let left_up = lines[i - 1][j - 1];
-                let right_down = lines[i + 1][j + 1];
-                let right_up = lines[i - 1][j + 1];
-                let left_down = lines[i + 1][j - 1];
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let grid = Grid::from_str(&contents)?;
+        let variants = Template::from_str(params.text("template")?)?.variants();
 
-                #[inline]
-                fn is_ms(c1: char, c2: char) -> bool {
-                    c1 == 'M' && c2 == 'S' || c1 == 'S' && c2 == 'M'
-                }
-                if is_ms(left_up, right_down) && is_ms(right_up, left_down) {
-                    occurances += 1;
-                }
+        let matches = find_template(&grid, &variants);
+        if params.flag("print")? {
+            for (position, variant) in &matches {
+                println!("variant {variant} at {position:?}");
             }
         }
-        Ok(occurances as i64)
+        Ok(matches.len() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::aoc_core::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+
+        assert_eq!(Day04.solve_a(contents.clone())?, 18);
+        assert_eq!(Day04.solve_b(contents)?, 9);
+        Ok(())
     }
 }
@@ -0,0 +1,217 @@
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+/// All 8 directions as `(dy, dx)`, clockwise starting with east
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+const DIRECTION_NAMES: [&str; 8] = ["E", "SE", "S", "SW", "W", "NW", "N", "NE"];
+
+pub struct Grid {
+    cells: Vec<Vec<char>>,
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .lines()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        if cells.is_empty() {
+            return Err(String::from("empty grid"));
+        }
+        Ok(Grid { cells })
+    }
+}
+
+impl Grid {
+    fn get(&self, y: isize, x: isize) -> Option<char> {
+        let y = usize::try_from(y).ok()?;
+        let x = usize::try_from(x).ok()?;
+        self.cells.get(y)?.get(x).copied()
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| (y, x)))
+    }
+}
+
+/// A word spelled from `start` on, one char per step in `direction`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordMatch<'a> {
+    pub start: (usize, usize),
+    pub direction: (isize, isize),
+    pub word: &'a str,
+}
+impl Display for WordMatch<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = DIRECTIONS
+            .iter()
+            .position(|&d| d == self.direction)
+            .map_or("?", |i| DIRECTION_NAMES[i]);
+        write!(f, "{} at {:?} going {name}", self.word, self.start)
+    }
+}
+
+/// Every occurrence of every word in all 8 directions. Words reading the same backwards are
+/// found twice, once per direction.
+pub fn find_words<'a>(grid: &Grid, words: &[&'a str]) -> Vec<WordMatch<'a>> {
+    let mut matches = Vec::new();
+    for (y, x) in grid.positions() {
+        for &direction in &DIRECTIONS {
+            for &word in words {
+                let spelled = word.chars().enumerate().all(|(i, c)| {
+                    let (dy, dx) = (direction.0 * i as isize, direction.1 * i as isize);
+                    grid.get(y as isize + dy, x as isize + dx) == Some(c)
+                });
+                if spelled && !word.is_empty() {
+                    matches.push(WordMatch {
+                        start: (y, x),
+                        direction,
+                        word,
+                    });
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// Rectangular pattern of chars, `None` matching any char
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    /// Rows separated by `/` or line breaks, `.` being the wildcard, like `M.S/.A./M.S`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .split(['/', '\n'])
+            .filter(|row| !row.is_empty())
+            .map(|row| {
+                row.chars()
+                    .map(|c| (c != '.').then_some(c))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if cells.is_empty() || cells.iter().any(|row| row.len() != cells[0].len()) {
+            return Err(format!("template {s} is not a non-empty rectangle"));
+        }
+        Ok(Template { cells })
+    }
+}
+
+impl Template {
+    fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn width(&self) -> usize {
+        self.cells[0].len()
+    }
+
+    fn rotated(&self) -> Template {
+        let cells = (0..self.width())
+            .map(|x| (0..self.height()).rev().map(|y| self.cells[y][x]).collect())
+            .collect();
+        Template { cells }
+    }
+
+    fn reflected(&self) -> Template {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+        Template { cells }
+    }
+
+    /// All distinct rotations and reflections, starting with the template itself
+    pub fn variants(&self) -> Vec<Template> {
+        let mut seen = HashSet::new();
+        let mut variants = Vec::new();
+        for mut variant in [self.clone(), self.reflected()] {
+            for _ in 0..4 {
+                if seen.insert(variant.clone()) {
+                    variants.push(variant.clone());
+                }
+                variant = variant.rotated();
+            }
+        }
+        variants
+    }
+
+    fn matches_at(&self, grid: &Grid, (y, x): (usize, usize)) -> bool {
+        self.cells.iter().enumerate().all(|(ty, row)| {
+            row.iter().enumerate().all(|(tx, &cell)| {
+                let c = grid.get((y + ty) as isize, (x + tx) as isize);
+                c.is_some() && (cell.is_none() || c == cell)
+            })
+        })
+    }
+}
+
+/// Top left corner of every place matching any variant, together with the variant's index
+pub fn find_template(grid: &Grid, variants: &[Template]) -> Vec<((usize, usize), usize)> {
+    grid.positions()
+        .flat_map(|position| {
+            variants
+                .iter()
+                .enumerate()
+                .filter(move |(_, variant)| variant.matches_at(grid, position))
+                .map(move |(i, _)| (position, i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_words() -> Result<(), String> {
+        let grid = Grid::from_str("XMAS\nAXAS\nOXMO\n")?;
+
+        let matches = find_words(&grid, &["XMAS", "AXO", "SAX"]);
+
+        assert_eq!(
+            matches.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+            vec![
+                "XMAS at (0, 0) going E",
+                "AXO at (0, 2) going SW",
+                "SAX at (0, 3) going SW",
+                "SAX at (1, 3) going W"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_variants() -> Result<(), String> {
+        let x_mas = Template::from_str("M.S/.A./M.S")?;
+        let corner = Template::from_str("AB/C.")?;
+
+        assert_eq!(x_mas.variants().len(), 4);
+        assert_eq!(corner.variants().len(), 8);
+        assert_eq!(
+            Template::from_str("AB/C.")?.rotated(),
+            Template::from_str("CA/.B")?
+        );
+        assert!(Template::from_str("AB/C").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt::Display,
+};
+
+use itertools::Itertools;
+
+/// `before|after`: if both pages are part of an update, `before` has to come first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub before: i32,
+    pub after: i32,
+}
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.before, self.after)
+    }
+}
+
+/// Pages of an update whose rules contradict each other, in the order they follow each other.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<i32>);
+impl Display for Cycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules = self
+            .0
+            .iter()
+            .zip(self.0.iter().cycle().skip(1))
+            .map(|(&before, &after)| Rule { before, after }.to_string());
+        write!(f, "cyclic rules {}", rules.collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Ordering rules as edges from every page to the pages that have to come after it. The
+/// whole graph does not need to be acyclic, only the part an update is restricted to.
+pub struct RuleGraph {
+    afters: HashMap<i32, HashSet<i32>>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &[Rule]) -> RuleGraph {
+        let mut afters: HashMap<i32, HashSet<i32>> = HashMap::new();
+        for rule in rules {
+            afters.entry(rule.before).or_default().insert(rule.after);
+        }
+        RuleGraph { afters }
+    }
+
+    /// Pages that have to come after `page` and are part of `pages`
+    fn afters_in<'a>(
+        &'a self,
+        page: i32,
+        pages: &'a HashMap<i32, usize>,
+    ) -> impl Iterator<Item = i32> + 'a {
+        self.afters
+            .get(&page)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|after| pages.contains_key(after))
+    }
+
+    /// Every rule the update breaks, ordered by the position of its `before` page.
+    pub fn violations(&self, update: &[i32]) -> Vec<Rule> {
+        let positions = positions(update);
+        let mut violations = Vec::new();
+        for (i, &before) in update.iter().enumerate() {
+            let mut broken = self
+                .afters_in(before, &positions)
+                .filter(|after| positions[after] < i)
+                .collect::<Vec<_>>();
+            broken.sort_by_key(|after| positions[after]);
+            violations.extend(broken.into_iter().map(|after| Rule { before, after }));
+        }
+        violations
+    }
+
+    pub fn is_ordered(&self, update: &[i32]) -> bool {
+        self.violations(update).is_empty()
+    }
+
+    /// Topological sort of the update's pages using only rules between them. Pages that are
+    /// free to go anywhere keep their relative order.
+    pub fn sort(&self, update: &[i32]) -> Result<Vec<i32>, Cycle> {
+        let positions = positions(update);
+        let mut in_degrees = vec![0usize; update.len()];
+        for &page in update {
+            for after in self.afters_in(page, &positions) {
+                in_degrees[positions[&after]] += 1;
+            }
+        }
+        let mut ready = (0..update.len())
+            .filter(|&i| in_degrees[i] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut sorted = Vec::with_capacity(update.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            sorted.push(update[i]);
+            for after in self.afters_in(update[i], &positions) {
+                let j = positions[&after];
+                in_degrees[j] -= 1;
+                if in_degrees[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+        if sorted.len() < update.len() {
+            let remaining = (0..update.len()).filter(|&i| in_degrees[i] > 0);
+            return Err(self.find_cycle(remaining.map(|i| update[i]).collect()));
+        }
+        Ok(sorted)
+    }
+
+    /// Every page left over by the sort has a predecessor that is left over as well, so
+    /// walking predecessors has to run into a cycle.
+    fn find_cycle(&self, remaining: HashSet<i32>) -> Cycle {
+        let mut predecessors = HashMap::new();
+        for &before in remaining.iter().sorted() {
+            for after in self.afters.get(&before).into_iter().flatten() {
+                if remaining.contains(after) {
+                    predecessors.entry(*after).or_insert(before);
+                }
+            }
+        }
+        let mut page = *remaining.iter().min().unwrap();
+        let mut seen = Vec::new();
+        while !seen.contains(&page) {
+            seen.push(page);
+            page = predecessors[&page];
+        }
+        let start = seen.iter().position(|&p| p == page).unwrap();
+        let mut cycle = seen.split_off(start);
+        cycle.reverse();
+        let smallest = cycle.iter().position_min().unwrap();
+        cycle.rotate_left(smallest);
+        Cycle(cycle)
+    }
+}
+
+fn positions(update: &[i32]) -> HashMap<i32, usize> {
+    update
+        .iter()
+        .enumerate()
+        .map(|(i, &page)| (page, i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(rules: &[(i32, i32)]) -> RuleGraph {
+        let rules = rules
+            .iter()
+            .map(|&(before, after)| Rule { before, after })
+            .collect::<Vec<_>>();
+        RuleGraph::new(&rules)
+    }
+
+    #[test]
+    fn test_violations() {
+        let graph = graph(&[(1, 2), (2, 3), (1, 3), (4, 1)]);
+
+        let violations = graph.violations(&[3, 2, 1]);
+
+        assert_eq!(
+            violations.iter().map(Rule::to_string).collect::<Vec<_>>(),
+            vec!["2|3", "1|3", "1|2"]
+        );
+        assert!(graph.is_ordered(&[1, 5, 2, 3]));
+        assert_eq!(graph.sort(&[3, 5, 2, 1]), Ok(vec![5, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_cycle_outside_of_update_is_fine() {
+        let graph = graph(&[(1, 2), (2, 3), (3, 1)]);
+
+        assert_eq!(graph.sort(&[2, 1]), Ok(vec![1, 2]));
+        assert_eq!(graph.sort(&[3, 4, 2, 1]), Err(Cycle(vec![1, 2, 3])));
+        assert_eq!(
+            Cycle(vec![1, 2, 3]).to_string(),
+            "cyclic rules 1|2, 2|3, 3|1"
+        );
+    }
+}
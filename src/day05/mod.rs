@@ -1,131 +1,110 @@
-use std::collections::HashMap;
-
+use graph::RuleGraph;
 use itertools::Itertools;
+use parse::{parse_input, Input};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
 
-pub struct Day05;
+mod graph;
+mod parse;
 
-type OrderingRules = HashMap<i32, Vec<i32>>;
+pub struct Day05;
 
-struct Input {
-    ordering_rules: OrderingRules,
-    updates_list: Vec<Vec<i32>>,
+fn middle_page(updates: &[i32]) -> i32 {
+    updates[updates.len().div_ceil(2) - 1]
 }
 
-fn parse_input(contents: String) -> Result<Input, String> {
-    let mut lines = contents.lines().into_iter();
-    let mut ordering_rules: OrderingRules = HashMap::new();
-    loop {
-        match lines.next() {
-            Some(line) => {
-                if line.trim().is_empty() {
-                    break;
-                }
-                let (before, after) = line
-                    .split("|")
-                    .map(|n| n.parse().unwrap())
-                    .collect_tuple::<(i32, i32)>()
-                    .unwrap();
-                ordering_rules.entry(before).or_default().push(after);
-            }
-            None => return Err(String::from("Unexpected EOF")),
-        }
-    }
-    let mut updates_list: Vec<Vec<i32>> = Vec::new();
-    loop {
-        match lines.next() {
-            Some(line) => {
-                updates_list.push(line.split(",").map(|v| v.parse().unwrap()).collect_vec());
-            }
-            None => {
-                break;
-            }
+/// Prints the rules every incorrectly ordered update breaks.
+fn explain(graph: &RuleGraph, updates_list: &[Vec<i32>]) {
+    for updates in updates_list {
+        let violations = graph.violations(updates);
+        if !violations.is_empty() {
+            println!(
+                "{} violates {}",
+                updates.iter().join(","),
+                violations.iter().join(" ")
+            );
         }
     }
-    if updates_list.len() == 0 {
-        return Err(String::from("Unexpected EOF"));
-    }
-    Ok(Input {
-        ordering_rules,
-        updates_list,
-    })
 }
 
-fn is_correct_order(ordering_rules: &OrderingRules, updates: &Vec<i32>) -> bool {
-    for i in 1..updates.len() {
-        let page = updates[i];
-        match ordering_rules.get(&page) {
-            Some(afters) => {
-                let before = &updates[0..i];
-                if afters.iter().any(|after| before.contains(after)) {
-                    return false;
-                }
-            }
-            None => (),
-        }
+/// Parses the input and prints its warnings, plus explanations if asked for.
+fn prepare(contents: String, params: &Params) -> Result<(RuleGraph, Vec<Vec<i32>>), String> {
+    let Input {
+        ordering_rules,
+        updates_list,
+        warnings,
+    } = parse_input(contents)?;
+    for warning in warnings {
+        println!("warning: {warning}");
+    }
+    let graph = RuleGraph::new(&ordering_rules);
+    if params.flag("explain")? {
+        explain(&graph, &updates_list);
     }
-    true
+    Ok((graph, updates_list))
 }
 
 impl AocTask for Day05 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let Input {
-            ordering_rules,
-            updates_list,
-        } = parse_input(contents)?;
-        let mut result = 0;
-        for updates in updates_list {
-            if is_correct_order(&ordering_rules, &updates) {
-                let middle_index = updates.len().div_ceil(2) - 1;
-                result += updates.get(middle_index).unwrap();
-            }
-        }
+    fn params(&self, _part: &TaskPart) -> Vec<ParamSpec> {
+        vec![
+            // prints the rules broken by every incorrectly ordered update
+            ParamSpec::new("explain", ParamKind::Flag, "false"),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let (graph, updates_list) = prepare(contents, params)?;
+        let result: i32 = updates_list
+            .iter()
+            .filter(|updates| graph.is_ordered(updates))
+            .map(|updates| middle_page(updates))
+            .sum();
         Ok(result as i64)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let Input {
-            ordering_rules,
-            ref mut updates_list,
-        } = parse_input(contents)?;
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let (graph, updates_list) = prepare(contents, params)?;
         let mut result = 0;
-        for updates in updates_list {
-            let mut was_incorrect = false;
-            let mut i = 1;
-            let n_updates = updates.len();
-            while i < n_updates {
-                let page = updates[i];
-
-                if let Some(afters) = ordering_rules.get(&page) {
-                    let before = &updates[0..i];
-
-                    if let Some(min_after_index) = afters
-                        .iter()
-                        .map(|after| {
-                            before
-                                .iter()
-                                .enumerate()
-                                .find(|&(_, b)| b == after)
-                                .map(|(index, _)| index)
-                        })
-                        .filter_map(|o| o)
-                        .min()
-                    {
-                        was_incorrect = true;
-                        updates.remove(i);
-                        updates.insert(min_after_index, page);
-                        i = min_after_index;
-                    }
-                }
-                i += 1;
-            }
-            if !was_incorrect {
+        for (i, updates) in updates_list.iter().enumerate() {
+            if graph.is_ordered(updates) {
                 continue;
             }
-            let middle_index = updates.len().div_ceil(2) - 1;
-            result += updates.get(middle_index).unwrap();
+            let sorted = graph
+                .sort(updates)
+                .map_err(|cycle| format!("update {} has {cycle}", i + 1))?;
+            result += middle_page(&sorted);
         }
         Ok(result as i64)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::aoc_core::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+
+        assert_eq!(Day05.solve_a(contents.clone())?, 143);
+        assert_eq!(Day05.solve_b(contents)?, 123);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cyclic_update() {
+        let result = Day05.solve_b(String::from("1|2\n2|3\n3|1\n\n3,1,2\n"));
+
+        assert_eq!(
+            result,
+            Err(String::from("update 1 has cyclic rules 1|2, 2|3, 3|1"))
+        );
+    }
+}
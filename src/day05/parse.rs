@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+
+use super::graph::Rule;
+
+pub struct Input {
+    pub ordering_rules: Vec<Rule>,
+    pub updates_list: Vec<Vec<i32>>,
+    /// Problems that do not stop the puzzle from being solved, with their line number
+    pub warnings: Vec<String>,
+}
+
+/// Rules, a blank line and updates. Every problem is reported with its 1-based line number.
+/// Lines may end with `\r\n`, trailing blank lines are ignored.
+pub fn parse_input(contents: String) -> Result<Input, String> {
+    let mut lines = contents.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let mut ordering_rules = Vec::new();
+    let mut rule_lines: HashMap<Rule, usize> = HashMap::new();
+    loop {
+        let Some((n, line)) = lines.next() else {
+            return Err(String::from(
+                "unexpected end of input, expected a blank line after the rules",
+            ));
+        };
+        if line.is_empty() {
+            break;
+        }
+        let rule = parse_rule(line).map_err(|e| format!("line {n}: {e}"))?;
+        if let Some(first) = rule_lines.insert(rule, n) {
+            return Err(format!(
+                "line {n}: duplicate rule {rule}, first given on line {first}"
+            ));
+        }
+        ordering_rules.push(rule);
+    }
+
+    let mut updates = Vec::new();
+    for (n, line) in lines {
+        updates.push((n, parse_update(line).map_err(|e| format!("line {n}: {e}"))?));
+    }
+    while updates.last().is_some_and(|(_, update)| update.is_empty()) {
+        updates.pop();
+    }
+    if updates.is_empty() {
+        return Err(String::from("unexpected end of input, expected updates"));
+    }
+
+    let in_rules: HashSet<i32> = ordering_rules
+        .iter()
+        .flat_map(|rule| [rule.before, rule.after])
+        .collect();
+    let mut warnings = Vec::new();
+    for (n, update) in &updates {
+        if update.is_empty() {
+            return Err(format!("line {n}: empty update"));
+        }
+        if update.len() % 2 == 0 {
+            return Err(format!(
+                "line {n}: update has {} pages and no middle page",
+                update.len()
+            ));
+        }
+        for page in update.iter().filter(|page| !in_rules.contains(page)) {
+            warnings.push(format!("line {n}: page {page} appears in no rule"));
+        }
+    }
+    Ok(Input {
+        ordering_rules,
+        updates_list: updates.into_iter().map(|(_, update)| update).collect(),
+        warnings,
+    })
+}
+
+fn parse_page(s: &str) -> Result<i32, String> {
+    s.parse().map_err(|_| format!("invalid page number `{s}`"))
+}
+
+fn parse_rule(line: &str) -> Result<Rule, String> {
+    let (before, after) = line
+        .split('|')
+        .collect_tuple()
+        .ok_or_else(|| format!("malformed rule `{line}`, expected `before|after`"))?;
+    let rule = Rule {
+        before: parse_page(before)?,
+        after: parse_page(after)?,
+    };
+    if rule.before == rule.after {
+        return Err(format!("rule {rule} orders a page before itself"));
+    }
+    Ok(rule)
+}
+
+fn parse_update(line: &str) -> Result<Vec<i32>, String> {
+    if line.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pages: Vec<i32> = line.split(',').map(parse_page).try_collect()?;
+    if let Some(page) = pages.iter().duplicates().next() {
+        return Err(format!("page {page} appears twice"));
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(contents: &str) -> String {
+        parse_input(contents.to_owned()).err().unwrap()
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        assert_eq!(
+            error("1|2\n1 |3\n\n1,2,3\n"),
+            "line 2: invalid page number `1 `"
+        );
+        assert_eq!(
+            error("1|2\n1-3\n\n1,2,3\n"),
+            "line 2: malformed rule `1-3`, expected `before|after`"
+        );
+        assert_eq!(
+            error("1|2\n2|3\n1|2\n\n1,2,3\n"),
+            "line 3: duplicate rule 1|2, first given on line 1"
+        );
+        assert_eq!(
+            error("1|1\n\n1,2,3\n"),
+            "line 1: rule 1|1 orders a page before itself"
+        );
+        assert_eq!(error("1|2\n\n1,2,3\n\n2,1,3\n"), "line 4: empty update");
+        assert_eq!(
+            error("1|2\n\n1,2,3\n2,1\n"),
+            "line 4: update has 2 pages and no middle page"
+        );
+        assert_eq!(error("1|2\n\n1,2,1\n"), "line 3: page 1 appears twice");
+        assert_eq!(
+            error("1|2\n2|3\n"),
+            "unexpected end of input, expected a blank line after the rules"
+        );
+    }
+
+    #[test]
+    fn test_windows_line_endings_and_warnings() -> Result<(), String> {
+        let input = parse_input(String::from("1|2\r\n2|3\r\n\r\n1,2,3\r\n3,4,1\r\n\r\n"))?;
+
+        assert_eq!(input.ordering_rules.len(), 2);
+        assert_eq!(input.updates_list, vec![vec![1, 2, 3], vec![3, 4, 1]]);
+        assert_eq!(input.warnings, vec!["line 5: page 4 appears in no rule"]);
+        Ok(())
+    }
+}
@@ -4,14 +4,9 @@ pub const RIGHT: Direction = (0, 1);
 pub const DOWN: Direction = (1, 0);
 pub const LEFT: Direction = (0, -1);
 
-#[inline]
-pub fn turn_90_deg((dy, dx): Direction) -> Direction {
-    (dx, -dy)
-}
-
 #[inline]
 pub fn move_and_get<P>(
-    map: &Vec<Vec<P>>,
+    map: &[Vec<P>],
     y: usize,
     x: usize,
     (dy, dx): Direction,
@@ -0,0 +1,331 @@
+use std::{ops::Shl, str::FromStr};
+
+use crate::aoc_util::gcd;
+
+use super::core::{move_and_get, Direction as DirectionDelta, DOWN, LEFT, RIGHT, UP};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PositionType {
+    Free,
+    Visited(u8),
+    Obstruction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up = 0,
+    Right = 1,
+    Down = 2,
+    Left = 3,
+}
+impl From<Direction> for u8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+        }
+    }
+}
+impl Shl<Direction> for u8 {
+    type Output = u8;
+
+    fn shl(self, rhs: Direction) -> Self::Output {
+        self << <u8>::from(rhs)
+    }
+}
+impl Direction {
+    pub fn turn_90_deg(self) -> Direction {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+    fn from_symbol(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Self::Up),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            '<' => Some(Self::Left),
+            _ => None,
+        }
+    }
+    fn symbol(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+    pub fn as_deltas(self) -> DirectionDelta {
+        match self {
+            Self::Up => UP,
+            Self::Right => RIGHT,
+            Self::Down => DOWN,
+            Self::Left => LEFT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Right,
+    Left,
+}
+impl Turn {
+    /// Number of clockwise quarter turns
+    pub fn quarters(self) -> usize {
+        match self {
+            Self::Right => 1,
+            Self::Left => 3,
+        }
+    }
+    fn apply(self, direction: Direction) -> Direction {
+        match self {
+            Self::Right => direction.turn_90_deg(),
+            Self::Left => direction.turn_90_deg().turn_90_deg().turn_90_deg(),
+        }
+    }
+}
+
+/// Turns a guard takes at obstructions, cycling through them one obstruction at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnRule(pub Vec<Turn>);
+impl FromStr for TurnRule {
+    type Err = String;
+
+    /// `right`, `left` or a comma-separated cycle of both like `right,right,left`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let turns = s
+            .split(',')
+            .map(|turn| match turn.trim() {
+                "right" | "r" => Ok(Turn::Right),
+                "left" | "l" => Ok(Turn::Left),
+                _ => Err(format!("invalid turn: {turn}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TurnRule(turns))
+    }
+}
+
+pub struct Input {
+    pub map: Vec<Vec<PositionType>>,
+    /// Starting position and direction of every guard, in reading order
+    pub guards: Vec<((usize, usize), Direction)>,
+}
+
+pub fn parse_input(contents: String) -> Result<Input, String> {
+    let mut map = Vec::new();
+    let mut guards = Vec::new();
+    for (y, line) in contents.lines().enumerate() {
+        let mut row = Vec::new();
+        for (x, c) in line.chars().enumerate() {
+            let position_type = match (c, Direction::from_symbol(c)) {
+                (_, Some(direction)) => {
+                    guards.push(((y, x), direction));
+                    PositionType::Visited(1 << direction)
+                }
+                ('.', _) => PositionType::Free,
+                ('#', _) => PositionType::Obstruction,
+                _ => return Err(String::from("invalid position type representation")),
+            };
+            row.push(position_type);
+        }
+        map.push(row);
+    }
+    if guards.is_empty() {
+        return Err(String::from("no starting position found"));
+    }
+    Ok(Input { map, guards })
+}
+
+/// States of a guard, one entry per step or turn, starting with the initial state.
+pub struct GuardPath {
+    pub steps: Vec<((usize, usize), Direction)>,
+    /// Position in the turn cycle at every step
+    pub phases: Vec<usize>,
+    /// Index of the first step the guard keeps coming back to, if it never leaves the map
+    pub loop_start: Option<usize>,
+}
+impl GuardPath {
+    pub fn looped(&self) -> bool {
+        self.loop_start.is_some()
+    }
+
+    /// Number of steps in one round of the loop
+    pub fn period(&self) -> Option<usize> {
+        self.loop_start.map(|start| self.steps.len() - start)
+    }
+}
+
+/// Walks the guard cell by cell, optionally with an `extra` obstruction placed on the map.
+/// Guards walk independently of each other. Visited states are kept as a direction bitmask
+/// per cell, one map of masks for every position in the turn cycle.
+pub fn guard_path(
+    input: &Input,
+    guard: usize,
+    rule: &TurnRule,
+    extra: Option<(usize, usize)>,
+) -> GuardPath {
+    let map = &input.map;
+    let ((mut y, mut x), mut direction) = input.guards[guard];
+    let width = map.first().map_or(0, |row| row.len());
+    let mut visited = vec![vec![vec![0u8; width]; map.len()]; rule.0.len()];
+    let mut phase = 0;
+    visited[phase][y][x] = 1 << direction;
+    let mut steps = vec![((y, x), direction)];
+    let mut phases = vec![phase];
+    while let Some((ny, nx, p)) = move_and_get(map, y, x, direction.as_deltas()) {
+        if *p == PositionType::Obstruction || extra == Some((ny, nx)) {
+            direction = rule.0[phase].apply(direction);
+            phase = (phase + 1) % rule.0.len();
+        } else {
+            (y, x) = (ny, nx);
+        }
+        if visited[phase][y][x] & (1 << direction) > 0 {
+            let loop_start = steps
+                .iter()
+                .zip(&phases)
+                .position(|(&step, &p)| step == ((y, x), direction) && p == phase);
+            return GuardPath {
+                steps,
+                phases,
+                loop_start,
+            };
+        }
+        visited[phase][y][x] |= 1 << direction;
+        steps.push(((y, x), direction));
+        phases.push(phase);
+    }
+    GuardPath {
+        steps,
+        phases,
+        loop_start: None,
+    }
+}
+
+/// All guards moving in lockstep, one step or turn per tick, repeat their combined state
+/// from `start` on every `period` ticks. A guard that left the map stays gone.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CombinedLoop {
+    pub start: usize,
+    pub period: usize,
+}
+
+pub fn combined_loop(paths: &[GuardPath]) -> Option<CombinedLoop> {
+    if !paths.iter().any(|path| path.looped()) {
+        return None;
+    }
+    let mut start = 0;
+    let mut period = 1;
+    for path in paths {
+        match (path.loop_start, path.period()) {
+            (Some(loop_start), Some(p)) => {
+                start = start.max(loop_start);
+                period = period / gcd(period, p) * p;
+            }
+            // leaves the map with the tick after its last step
+            _ => start = start.max(path.steps.len()),
+        }
+    }
+    Some(CombinedLoop { start, period })
+}
+
+/// Draws the map like the puzzle does: `|` and `-` where a guard walked vertically or
+/// horizontally, `+` where it did both and `O` for the given obstructions.
+pub fn render(input: &Input, paths: &[GuardPath], obstructions: &[(usize, usize)]) -> String {
+    let mut walked = vec![vec![0u8; input.map.first().map_or(0, |row| row.len())]; input.map.len()];
+    for &((y, x), direction) in paths.iter().flat_map(|path| &path.steps) {
+        walked[y][x] |= match direction {
+            Direction::Up | Direction::Down => 1,
+            Direction::Right | Direction::Left => 2,
+        };
+    }
+    let mut result = String::new();
+    for (y, row) in input.map.iter().enumerate() {
+        for (x, p) in row.iter().enumerate() {
+            let guard = input.guards.iter().find(|(start, _)| *start == (y, x));
+            result.push(match (walked[y][x], p) {
+                _ if obstructions.contains(&(y, x)) => 'O',
+                _ if guard.is_some() => guard.unwrap().1.symbol(),
+                (_, PositionType::Obstruction) => '#',
+                (1, _) => '|',
+                (2, _) => '-',
+                (3, _) => '+',
+                _ => '.',
+            });
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_GUARDS: &str = "\
+        .#...\n\
+        ....#\n\
+        .^...\n\
+        #....\n\
+        ...<.\n";
+
+    #[test]
+    fn test_guards_from_any_direction() -> Result<(), String> {
+        let input = parse_input(TWO_GUARDS.to_owned())?;
+
+        assert_eq!(
+            input.guards,
+            vec![((2, 1), Direction::Up), ((4, 3), Direction::Left)]
+        );
+        let path = guard_path(&input, 1, &TurnRule::from_str("right")?, None);
+        assert_eq!(path.steps.last(), Some(&((4, 0), Direction::Left)));
+        assert!(!path.looped());
+        Ok(())
+    }
+
+    #[test]
+    fn test_turn_rules() -> Result<(), String> {
+        let input = parse_input(TWO_GUARDS.to_owned())?;
+
+        // up to the wall, then right until the obstruction in row 1, then down forever
+        let right = guard_path(&input, 0, &TurnRule::from_str("right")?, None);
+        assert_eq!(right.loop_start, None);
+        let left = guard_path(&input, 0, &TurnRule::from_str("left")?, None);
+        assert_eq!(left.steps[2], ((1, 1), Direction::Left));
+
+        // a guard boxed in on a 2x2 square of free cells loops with either rule
+        let boxed = parse_input(".##.\n#^.#\n#..#\n.##.\n".to_owned())?;
+        for rule in ["right", "left,left", "right,right,right,left"] {
+            let path = guard_path(&boxed, 0, &TurnRule::from_str(rule)?, None);
+            assert!(path.looped(), "{rule}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_combined_loop() -> Result<(), String> {
+        let input = parse_input(".##.##.\n#^.##^#\n#..##.#\n.##....\n".to_owned())?;
+        let rule = TurnRule::from_str("right")?;
+
+        let paths = (0..input.guards.len())
+            .map(|guard| guard_path(&input, guard, &rule, None))
+            .collect::<Vec<_>>();
+
+        assert_eq!(paths[0].period(), Some(8));
+        assert_eq!(paths[1].loop_start, None);
+        assert_eq!(
+            combined_loop(&paths),
+            Some(CombinedLoop {
+                start: paths[1].steps.len().max(paths[0].loop_start.unwrap()),
+                period: 8
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+/// For every cell and direction, how far the guard walks before it has to turn or leaves the
+/// map. Lets a walk skip whole straight segments instead of going cell by cell.
+pub struct JumpTable {
+    height: usize,
+    width: usize,
+    /// `distances[(y * width + x) * 4 + direction]`
+    distances: Vec<Jump>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Jump {
+    /// Number of free cells before the next obstruction
+    Obstruction(usize),
+    /// Number of cells left until the edge of the map
+    Exit(usize),
+}
+impl Jump {
+    pub fn distance(self) -> usize {
+        match self {
+            Self::Obstruction(d) | Self::Exit(d) => d,
+        }
+    }
+}
+
+/// Directions in the order a right-turning guard cycles through them, as `(dy, dx)`.
+pub const DELTAS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+impl JumpTable {
+    pub fn new(obstructions: &[Vec<bool>]) -> JumpTable {
+        let height = obstructions.len();
+        let width = obstructions.first().map_or(0, |row| row.len());
+        let mut distances = vec![Jump::Exit(0); height * width * 4];
+        for (direction, &(dy, dx)) in DELTAS.iter().enumerate() {
+            // visit cells so that the neighbour in `direction` is already known
+            let ys: Vec<usize> = if dy > 0 {
+                (0..height).rev().collect()
+            } else {
+                (0..height).collect()
+            };
+            let xs: Vec<usize> = if dx > 0 {
+                (0..width).rev().collect()
+            } else {
+                (0..width).collect()
+            };
+            for &y in &ys {
+                for &x in &xs {
+                    let next = y
+                        .checked_add_signed(dy)
+                        .zip(x.checked_add_signed(dx))
+                        .filter(|&(ny, nx)| ny < height && nx < width);
+                    distances[(y * width + x) * 4 + direction] = match next {
+                        None => Jump::Exit(0),
+                        Some((ny, nx)) if obstructions[ny][nx] => Jump::Obstruction(0),
+                        Some((ny, nx)) => match distances[(ny * width + nx) * 4 + direction] {
+                            Jump::Obstruction(d) => Jump::Obstruction(d + 1),
+                            Jump::Exit(d) => Jump::Exit(d + 1),
+                        },
+                    };
+                }
+            }
+        }
+        JumpTable {
+            height,
+            width,
+            distances,
+        }
+    }
+
+    pub fn jump(&self, (y, x): (usize, usize), direction: usize) -> Jump {
+        self.distances[(y * self.width + x) * 4 + direction]
+    }
+
+    pub fn n_cells(&self) -> usize {
+        self.height * self.width
+    }
+
+    pub fn index(&self, (y, x): (usize, usize)) -> usize {
+        y * self.width + x
+    }
+
+    /// Whether the guard loops when starting at `start` facing `direction`, with an additional
+    /// obstruction placed at `extra`. At obstructions the guard turns by `turns[phase]`
+    /// clockwise quarters, cycling through `turns`. Only turning points are recorded in
+    /// `visited`, one bit per direction and `n_cells` masks per phase. The bits set are reset
+    /// again before returning.
+    pub fn loops_with_obstruction(
+        &self,
+        start: (usize, usize),
+        mut direction: usize,
+        mut phase: usize,
+        turns: &[usize],
+        extra: (usize, usize),
+        visited: &mut [u8],
+    ) -> bool {
+        let mut touched = Vec::new();
+        let mut position = start;
+        let looped = loop {
+            let mut jump = self.jump(position, direction);
+            if let Some(distance) = distance_to(position, direction, extra) {
+                if distance <= jump.distance() {
+                    jump = Jump::Obstruction(distance - 1);
+                }
+            }
+            let Jump::Obstruction(distance) = jump else {
+                break false;
+            };
+            position = step(position, direction, distance);
+
+            let index = phase * self.n_cells() + self.index(position);
+            if visited[index] & (1 << direction) > 0 {
+                break true;
+            }
+            visited[index] |= 1 << direction;
+            touched.push(index);
+            direction = (direction + turns[phase]) % 4;
+            phase = (phase + 1) % turns.len();
+        };
+        for index in touched {
+            visited[index] = 0;
+        }
+        looped
+    }
+}
+
+/// Number of steps from `from` in `direction` to reach `to`, if it lies ahead.
+fn distance_to(from: (usize, usize), direction: usize, to: (usize, usize)) -> Option<usize> {
+    let ((y, x), (ty, tx)) = (from, to);
+    match DELTAS[direction] {
+        (-1, 0) if tx == x && ty < y => Some(y - ty),
+        (1, 0) if tx == x && ty > y => Some(ty - y),
+        (0, -1) if ty == y && tx < x => Some(x - tx),
+        (0, 1) if ty == y && tx > x => Some(tx - x),
+        _ => None,
+    }
+}
+
+fn step((y, x): (usize, usize), direction: usize, distance: usize) -> (usize, usize) {
+    let (dy, dx) = DELTAS[direction];
+    (
+        y.wrapping_add_signed(dy * distance as isize),
+        x.wrapping_add_signed(dx * distance as isize),
+    )
+}
@@ -1,17 +1,48 @@
-use crate::aoc_core::{AocResult, AocTask};
+use std::str::FromStr;
+
+use guards::{parse_input, TurnRule};
+
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
 
 mod core;
+mod guards;
+mod jumps;
 mod part_a;
 mod part_b;
 
 pub struct Day06;
 
 impl AocTask for Day06 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        part_a::solve_a(contents)
+    fn params(&self, _part: &TaskPart) -> Vec<ParamSpec> {
+        vec![
+            // `right`, `left` or a cycle like `right,right,left`, one turn per obstruction
+            ParamSpec::new("turns", ParamKind::Text, "right"),
+            // prints the guards' paths, part B also marks all loop obstructions
+            ParamSpec::new("render", ParamKind::Flag, "false"),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let input = parse_input(contents)?;
+        let rule = TurnRule::from_str(params.text("turns")?)?;
+        let paths = part_a::walk_guards(&input, &rule);
+        if params.flag("render")? {
+            print!("{}", guards::render(&input, &paths, &[]));
+        }
+        part_a::solve_a(&paths)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        part_b::solve_b(contents)
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let input = parse_input(contents)?;
+        let rule = TurnRule::from_str(params.text("turns")?)?;
+        let obstructions = part_b::loop_obstructions(&input, &rule)?;
+        if params.flag("render")? {
+            let paths = part_a::walk_guards(&input, &rule);
+            print!("{}", guards::render(&input, &paths, &obstructions));
+        }
+        Ok(obstructions.len() as i64)
     }
 }
@@ -1,103 +1,43 @@
-use std::str::FromStr;
+use std::collections::HashSet;
 
 use crate::aoc_core::AocResult;
 
-use super::core::{move_and_get, turn_90_deg, Direction, UP};
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-enum PositionType {
-    Free,
-    Visited,
-    Obstruction,
-}
-impl FromStr for PositionType {
-    type Err = String;
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "." => Ok(Self::Free),
-            "#" => Ok(Self::Obstruction),
-            _ => Err(String::from("invalid position type representation")),
-        }
-    }
-}
-
-fn mark_visited<'a>(
-    map: &'a mut Vec<Vec<PositionType>>,
-    (mut y, mut x): (usize, usize),
-    mut direction: Direction,
-) {
-    loop {
-        match move_and_get(&map, y, x, direction) {
-            None => return,
-            Some((ny, nx, PositionType::Visited)) => {
-                (y, x) = (ny, nx);
-            }
-            Some((_, _, PositionType::Obstruction)) => {
-                direction = turn_90_deg(direction);
-            }
-            Some((ny, nx, PositionType::Free)) => {
-                map[ny][nx] = PositionType::Visited;
-                (y, x) = (ny, nx);
+use super::guards::{combined_loop, guard_path, GuardPath, Input, TurnRule};
+
+/// Walks every guard. With several guards or a looping one, prints how each of them ends and
+/// when their combined state starts repeating.
+pub fn walk_guards(input: &Input, rule: &TurnRule) -> Vec<GuardPath> {
+    let paths = (0..input.guards.len())
+        .map(|guard| guard_path(input, guard, rule, None))
+        .collect::<Vec<_>>();
+
+    if paths.len() > 1 || paths.iter().any(|path| path.looped()) {
+        for (guard, path) in paths.iter().enumerate() {
+            let ((y, x), _) = input.guards[guard];
+            match (path.loop_start, path.period()) {
+                (Some(start), Some(period)) => println!(
+                    "guard {guard} from {y},{x} loops after {start} steps every {period} steps"
+                ),
+                _ => println!(
+                    "guard {guard} from {y},{x} leaves after {} steps",
+                    path.steps.len()
+                ),
             }
         }
-    }
-}
-
-struct Input {
-    map: Vec<Vec<PositionType>>,
-    position: (usize, usize),
-    direction: Direction,
-}
-
-fn parse_input(contents: String) -> Result<Input, String> {
-    let mut map = Vec::new();
-    let mut position = None;
-    for (y, line) in contents.lines().enumerate() {
-        let mut row = Vec::new();
-        for (x, c) in line.chars().enumerate() {
-            let position_type = match c {
-                '^' => {
-                    position = Some((y, x));
-                    PositionType::Visited
-                }
-                c => c.to_string().as_str().parse::<PositionType>()?,
-            };
-            row.push(position_type);
+        if let Some(combined) = combined_loop(&paths) {
+            println!(
+                "all guards together loop after {} steps every {} steps",
+                combined.start, combined.period
+            );
         }
-        map.push(row);
     }
-    let position = position.ok_or(String::from("no starting position found"))?;
-    Ok(Input {
-        map,
-        position,
-        direction: UP,
-    })
+    paths
 }
 
-pub fn solve_a(contents: String) -> AocResult {
-    let Input {
-        mut map,
-        direction,
-        position,
-    } = parse_input(contents)?;
-
-    mark_visited(&mut map, position, direction);
-
-    let visited = map
+pub fn solve_a(paths: &[GuardPath]) -> AocResult {
+    let visited = paths
         .iter()
-        .map(|row| row.iter().filter(|&p| *p == PositionType::Visited).count() as i64)
-        .sum::<i64>();
-    // for row in map {
-    //     println!(
-    //         "{}",
-    //         row.iter()
-    //             .map(|c| match c {
-    //                 PositionType::Free => ".",
-    //                 PositionType::Visited => "X",
-    //                 PositionType::Obstruction => "#",
-    //             })
-    //             .join("")
-    //     )
-    // }
-    Ok(visited)
+        .flat_map(|path| path.steps.iter().map(|&(position, _)| position))
+        .collect::<HashSet<_>>();
+    Ok(visited.len() as i64)
 }
@@ -1,157 +1,293 @@
-use std::{ops::Shl, str::FromStr};
+use std::thread;
 
-use crate::aoc_core::AocResult;
+use super::{
+    guards::{guard_path, Input, PositionType, TurnRule},
+    jumps::JumpTable,
+};
 
-use super::core::{move_and_get, Direction as DirectionDelta, DOWN, LEFT, RIGHT, UP};
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-enum PositionType {
-    Free,
-    Visited(u8),
-    Obstruction,
-}
-impl FromStr for PositionType {
-    type Err = String;
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value {
-            "." => Ok(Self::Free),
-            "#" => Ok(Self::Obstruction),
-            "^" => Ok(Self::Visited(1 << Direction::Up)),
-            _ => Err(String::from("invalid position type representation")),
-        }
-    }
-}
+/// All positions where a single additional obstruction makes one of the guards loop, in the
+/// order the guards first reach them.
+pub fn loop_obstructions(input: &Input, rule: &TurnRule) -> Result<Vec<(usize, usize)>, String> {
+    let obstructions = input
+        .map
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|p| *p == PositionType::Obstruction)
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let jumps = JumpTable::new(&obstructions);
+    let turns = rule
+        .0
+        .iter()
+        .map(|turn| turn.quarters())
+        .collect::<Vec<_>>();
 
-#[derive(Clone, Copy)]
-enum Direction {
-    Up = 0,
-    Right = 1,
-    Down = 2,
-    Left = 3,
-}
-impl From<Direction> for u8 {
-    fn from(value: Direction) -> Self {
-        match value {
-            Direction::Up => 0,
-            Direction::Right => 1,
-            Direction::Down => 2,
-            Direction::Left => 3,
+    // every obstruction is tried at the first cell each guard steps on it, the walk of that
+    // guard with the obstruction then starts right before it
+    let mut candidates = Vec::new();
+    for guard in 0..input.guards.len() {
+        let path = guard_path(input, guard, rule, None);
+        if path.looped() {
+            return Err(format!("Guard {guard} is already looping"));
         }
-    }
-}
-impl Shl<Direction> for u8 {
-    type Output = u8;
-
-    fn shl(self, rhs: Direction) -> Self::Output {
-        self << <u8>::from(rhs)
-    }
-}
-impl Direction {
-    fn turn_90_deg(self) -> Direction {
-        match self {
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
+        let mut seen = vec![false; jumps.n_cells()];
+        for &(position, _) in &input.guards {
+            seen[jumps.index(position)] = true;
         }
-    }
-    fn as_deltas(self) -> DirectionDelta {
-        match self {
-            Self::Up => UP,
-            Self::Right => RIGHT,
-            Self::Down => DOWN,
-            Self::Left => LEFT,
+        for (i, window) in path.steps.windows(2).enumerate() {
+            let [(from, direction), (to, _)] = *window else {
+                unreachable!()
+            };
+            if from != to && !seen[jumps.index(to)] {
+                seen[jumps.index(to)] = true;
+                candidates.push((to, from, direction, path.phases[i]));
+            }
         }
     }
+
+    let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = candidates.len().div_ceil(n_threads).max(1);
+    let looping = thread::scope(|scope| {
+        let handles = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let (jumps, turns) = (&jumps, &turns);
+                scope.spawn(move || {
+                    let mut visited = vec![0; jumps.n_cells() * turns.len()];
+                    chunk
+                        .iter()
+                        .filter(|&&(obstruction, start, direction, phase)| {
+                            jumps.loops_with_obstruction(
+                                start,
+                                u8::from(direction) as usize,
+                                phase,
+                                turns,
+                                obstruction,
+                                &mut visited,
+                            )
+                        })
+                        .map(|&(obstruction, _, _, _)| obstruction)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    // an obstruction making several guards loop is only reported once
+    let mut reported = vec![false; jumps.n_cells()];
+    Ok(looping
+        .into_iter()
+        .filter(|&obstruction| !std::mem::replace(&mut reported[jumps.index(obstruction)], true))
+        .collect())
 }
 
-fn mark_visited_with_loop(
-    map: &mut Vec<Vec<PositionType>>,
-    (mut y, mut x): (usize, usize),
-    mut direction: Direction,
-) -> bool {
-    loop {
-        match move_and_get(&map, y, x, direction.as_deltas()) {
-            None => return false,
-            Some((ny, nx, PositionType::Visited(mask))) => {
-                if (mask & (1 << direction)) > 0 {
-                    return true;
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, str::FromStr};
+
+    use crate::{
+        aoc_core::{read_file, AocResult},
+        aoc_util::Random,
+    };
+
+    use super::super::{
+        core::move_and_get,
+        guards::{parse_input, render, Direction},
+    };
+    use super::*;
+
+    fn mark_visited_with_loop(
+        map: &mut [Vec<PositionType>],
+        (mut y, mut x): (usize, usize),
+        mut direction: Direction,
+    ) -> bool {
+        loop {
+            match move_and_get(map, y, x, direction.as_deltas()) {
+                None => return false,
+                Some((ny, nx, PositionType::Visited(mask))) => {
+                    if (mask & (1 << direction)) > 0 {
+                        return true;
+                    }
+                    map[ny][nx] = PositionType::Visited(mask | (1 << direction));
+                    (y, x) = (ny, nx);
+                }
+                Some((_, _, PositionType::Obstruction)) => {
+                    direction = direction.turn_90_deg();
+                }
+                Some((ny, nx, PositionType::Free)) => {
+                    map[ny][nx] = PositionType::Visited(1 << direction);
+                    (y, x) = (ny, nx);
                 }
-                map[ny][nx] = PositionType::Visited(mask | (1 << direction));
-                (y, x) = (ny, nx);
-            }
-            Some((_, _, PositionType::Obstruction)) => {
-                direction = direction.turn_90_deg();
-            }
-            Some((ny, nx, PositionType::Free)) => {
-                map[ny][nx] = PositionType::Visited(1 << direction);
-                (y, x) = (ny, nx);
             }
         }
     }
-}
 
-struct Input {
-    map: Vec<Vec<PositionType>>,
-    position: (usize, usize),
-    direction: Direction,
-}
+    /// The original implementation, walking a fresh copy of the map for every obstruction.
+    fn solve_b_cell_by_cell(contents: String) -> AocResult {
+        let Input { mut map, guards } = parse_input(contents)?;
+        let (starting_position, direction) = guards[0];
+        let original_map = map.to_vec();
+        if mark_visited_with_loop(&mut map, starting_position, direction) {
+            return Err(String::from("Guard is already looping"));
+        }
 
-fn parse_input(contents: String) -> Result<Input, String> {
-    let mut map = Vec::new();
-    let mut position = None;
-    for (y, line) in contents.lines().enumerate() {
-        let mut row = Vec::new();
-        for (x, c) in line.chars().enumerate() {
-            let position_type = PositionType::from_str(&c.to_string())?;
-            match position_type {
-                PositionType::Visited(_) => {
-                    position = Some((y, x));
+        let mut looping_obstruction_positions = 0;
+        for (y, row) in map.iter().enumerate() {
+            for (x, p) in row.iter().enumerate() {
+                if !matches!(p, PositionType::Visited(_)) || (y, x) == starting_position {
+                    continue;
+                }
+                let mut map = original_map.to_vec();
+                map[y][x] = PositionType::Obstruction;
+                if mark_visited_with_loop(&mut map, starting_position, direction) {
+                    looping_obstruction_positions += 1;
                 }
-                _ => (),
             }
-            row.push(position_type);
         }
-        map.push(row);
+        Ok(looping_obstruction_positions)
     }
-    let position = position.ok_or(String::from("no starting position found"))?;
-    Ok(Input {
-        map,
-        position,
-        direction: Direction::Up,
-    })
-}
 
-pub fn solve_b(contents: String) -> AocResult {
-    let Input {
-        mut map,
-        direction,
-        position: starting_position,
-    } = parse_input(contents)?;
-    let original_map = map.to_vec();
-    let already_looped = mark_visited_with_loop(&mut map, starting_position, direction);
-    if already_looped {
-        return Err(String::from("Guard is already looping"));
+    /// Tries every cell any guard walks over with the cell by cell simulation.
+    fn loop_obstructions_cell_by_cell(input: &Input, rule: &TurnRule) -> Vec<(usize, usize)> {
+        let paths = (0..input.guards.len())
+            .map(|guard| guard_path(input, guard, rule, None))
+            .collect::<Vec<_>>();
+        let mut obstructions = paths
+            .iter()
+            .flat_map(|path| path.steps.iter().map(|&(position, _)| position))
+            .filter(|position| input.guards.iter().all(|(start, _)| start != position))
+            .filter(|&extra| {
+                (0..input.guards.len())
+                    .any(|guard| guard_path(input, guard, rule, Some(extra)).looped())
+            })
+            .collect::<Vec<_>>();
+        obstructions.sort();
+        obstructions.dedup();
+        obstructions
     }
 
-    let possible_obstruction_positions = map.iter().enumerate().flat_map(|(y, row)| {
-        row.iter().enumerate().filter_map(move |(x, p)| match *p {
-            PositionType::Visited(_) if (y, x) != starting_position => Some((y, x)),
-            _ => None,
-        })
-    });
+    #[test]
+    fn test_render_loop_obstruction() -> Result<(), String> {
+        let input = parse_input(read_file(Path::new(file!()), "sample.txt"))?;
 
-    let mut looping_obstruction_positions = 0;
-    for (oy, ox) in possible_obstruction_positions {
-        let mut map = original_map.to_vec();
-        map[oy][ox] = PositionType::Obstruction;
+        let path = guard_path(&input, 0, &TurnRule::from_str("right")?, Some((6, 3)));
 
-        if mark_visited_with_loop(&mut map, starting_position, direction) {
-            looping_obstruction_positions += 1;
+        assert!(path.looped());
+        assert_eq!(
+            render(&input, &[path], &[(6, 3)]),
+            "....#.....\n\
+             ....+---+#\n\
+             ....|...|.\n\
+             ..#.|...|.\n\
+             ....|..#|.\n\
+             ....|...|.\n\
+             .#.O^---+.\n\
+             ........#.\n\
+             #.........\n\
+             ......#...\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_loop_obstructions_sample() -> Result<(), String> {
+        let input = parse_input(read_file(Path::new(file!()), "sample.txt"))?;
+
+        let mut obstructions = loop_obstructions(&input, &TurnRule::from_str("right")?)?;
+        obstructions.sort();
+
+        assert_eq!(
+            obstructions,
+            vec![(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_cell_by_cell() -> Result<(), String> {
+        for name in ["sample.txt", "input.txt"] {
+            let contents = read_file(Path::new(file!()), name);
+            assert_eq!(
+                loop_obstructions(
+                    &parse_input(contents.clone())?,
+                    &TurnRule::from_str("right")?
+                )?
+                .len() as i64,
+                solve_b_cell_by_cell(contents)?,
+                "{name}"
+            );
         }
+        Ok(())
     }
 
-    // TODO maybe faster to clean the existing vector
+    #[test]
+    fn test_guards_and_turn_rules_match_cell_by_cell() -> Result<(), String> {
+        let sample = read_file(Path::new(file!()), "sample.txt");
+        // a second guard facing left in the bottom right corner
+        let two_guards = sample.replacen("......#...", "......#..<", 1);
+        let mut checked = 0;
+        for contents in [&sample, &two_guards] {
+            let input = parse_input(contents.clone())?;
+            for rule in ["right", "left", "right,left,left"] {
+                let rule = TurnRule::from_str(rule)?;
+                let Ok(mut obstructions) = loop_obstructions(&input, &rule) else {
+                    continue;
+                };
+                obstructions.sort();
+                assert_eq!(
+                    obstructions,
+                    loop_obstructions_cell_by_cell(&input, &rule),
+                    "{rule:?}"
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked >= 4);
+        Ok(())
+    }
 
-    Ok(looping_obstruction_positions)
+    /// Random maps with a few obstructions and two or three guards facing any direction.
+    fn random_map(random: &mut Random) -> String {
+        let (height, width) = (4 + random.below(6), 4 + random.below(6));
+        let mut cells = (0..height * width)
+            .map(|_| if random.below(6) == 0 { '#' } else { '.' })
+            .collect::<Vec<_>>();
+        for _ in 0..2 + random.below(2) {
+            cells[random.below(height * width)] = ['^', '>', 'v', '<'][random.below(4)];
+        }
+        cells
+            .chunks(width)
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn test_random_guards_match_cell_by_cell() -> Result<(), String> {
+        let mut random = Random::new(2024);
+        let mut checked = 0;
+        for _ in 0..500 {
+            let contents = random_map(&mut random);
+            let input = parse_input(contents.clone())?;
+            for rule in ["right", "left", "right,left,left"] {
+                let rule = TurnRule::from_str(rule)?;
+                let Ok(mut obstructions) = loop_obstructions(&input, &rule) else {
+                    continue;
+                };
+                obstructions.sort();
+                assert_eq!(
+                    obstructions,
+                    loop_obstructions_cell_by_cell(&input, &rule),
+                    "{rule:?} on\n{contents}"
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked >= 1000);
+        Ok(())
+    }
 }
@@ -1,115 +1,146 @@
-use std::{collections::VecDeque, num::TryFromIntError};
+use std::num::TryFromIntError;
 
 use itertools::Itertools;
+use solver::{analyse, evaluate, operator, solve, Operator};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod solver;
 
 pub struct Day07;
 
-fn is_solvable_a(expected_result: u64, mut values: VecDeque<u64>) -> bool {
-    fn _is_solvable(
-        expected_result: u64,
-        intermediate_result: u64,
-        mut values: VecDeque<u64>,
-    ) -> bool {
-        if intermediate_result > expected_result {
-            return false;
-        }
-        match values.pop_front() {
-            None => return expected_result == intermediate_result,
-            Some(val) => {
-                _is_solvable(expected_result, intermediate_result * val, values.clone())
-                    || _is_solvable(expected_result, intermediate_result + val, values)
-            }
-        }
+struct Equation {
+    result: u128,
+    values: Vec<u128>,
+}
+
+fn parse_input(contents: String) -> Result<Vec<Equation>, String> {
+    contents
+        .lines()
+        .map(|line| {
+            let (raw_result, raw_values) = line.split_once(":").ok_or("Invalid line")?;
+            let result = raw_result.parse::<u128>().map_err(|e| e.to_string())?;
+            let values = raw_values
+                .split_whitespace()
+                .map(|s| s.parse::<u128>())
+                .try_collect()
+                .map_err(|e| e.to_string())?;
+            Ok(Equation { result, values })
+        })
+        .collect()
+}
+
+/// Like `3267: 81 + 40 * 27`
+fn format_solution(
+    equation: &Equation,
+    operators: &[Box<dyn Operator>],
+    witness: &[usize],
+) -> String {
+    let mut result = format!("{}: {}", equation.result, equation.values[0]);
+    for (value, &i) in equation.values[1..].iter().zip(witness) {
+        result += &format!(" {} {value}", operators[i].symbol());
+    }
+    result
+}
+
+impl AocTask for Day07 {
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let operators = match part {
+            TaskPart::A => "add,mul",
+            TaskPart::B => "add,mul,concat",
+        };
+        vec![
+            // comma-separated list of add, mul, concat, sub and xor
+            ParamSpec::new("operators", ParamKind::Text, operators),
+            // prints every solvable equation with the operators that solve it
+            ParamSpec::new("print", ParamKind::Flag, "false"),
+            // prints the number of solving assignments and the reachable range of every equation
+            ParamSpec::new("analyse", ParamKind::Flag, "false"),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        calibrate(contents, params)
     }
 
-    match values.pop_front() {
-        None => false,
-        Some(first) => _is_solvable(expected_result, first, values),
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        calibrate(contents, params)
     }
 }
 
-fn is_solvable_b(expected_result: u64, mut values: VecDeque<u64>) -> bool {
-    fn _is_solvable(
-        expected_result: u64,
-        intermediate_result: u64,
-        mut values: VecDeque<u64>,
-    ) -> bool {
-        if intermediate_result > expected_result {
-            return false;
+fn calibrate(contents: String, params: &Params) -> AocResult {
+    let operators: Vec<_> = params
+        .text("operators")?
+        .split(',')
+        .map(operator)
+        .try_collect()?;
+    let print = params.flag("print")?;
+    let analyse_equations = params.flag("analyse")?;
+
+    let mut total_result = 0u128;
+    for equation in parse_input(contents)? {
+        if analyse_equations {
+            let analysis = analyse(equation.result, &equation.values, &operators);
+            let reachable = match analysis.reachable {
+                Some((min, max)) => format!("{min}..={max}"),
+                None => String::from("nothing"),
+            };
+            println!(
+                "{}: {} solutions, reaches {reachable}",
+                equation.result, analysis.solutions
+            );
         }
-        match values.pop_front() {
-            None => return expected_result == intermediate_result,
-            Some(val) => {
-                _is_solvable(
-                    expected_result,
-                    intermediate_result * (10u64.pow(val.ilog10() + 1)) + val,
-                    values.clone(),
-                ) || _is_solvable(expected_result, intermediate_result * val, values.clone())
-                    || _is_solvable(expected_result, intermediate_result + val, values)
-            }
+        let Some(witness) = solve(equation.result, &equation.values, &operators) else {
+            continue;
+        };
+        debug_assert_eq!(
+            evaluate(&equation.values, &operators, &witness),
+            Some(equation.result)
+        );
+        if print {
+            println!("{}", format_solution(&equation, &operators, &witness));
         }
+        total_result = total_result
+            .checked_add(equation.result)
+            .ok_or("total calibration result overflows")?;
     }
 
-    match values.pop_front() {
-        None => false,
-        Some(first) => _is_solvable(expected_result, first, values),
-    }
+    total_result
+        .try_into()
+        .map_err(|e: TryFromIntError| e.to_string())
 }
 
-impl AocTask for Day07 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut equations = Vec::new();
-        for line in contents.lines() {
-            let (raw_result, raw_values) = line.split_once(":").ok_or("Invalid line")?;
-            let result = raw_result.parse::<u64>().map_err(|e| e.to_string())?;
-            let values: VecDeque<_> = raw_values
-                .trim()
-                .split_whitespace()
-                .map(|s| s.parse::<u64>())
-                .try_collect()
-                .map_err(|e| e.to_string())?;
-            equations.push((result, values));
-        }
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
 
-        let mut total_result = 0u64;
+    use crate::aoc_core::read_file;
 
-        for (expected_result, values) in equations {
-            if is_solvable_a(expected_result, values) {
-                total_result += expected_result;
-            }
-        }
+    use super::*;
 
-        total_result
-            .try_into()
-            .map_err(|e: TryFromIntError| e.to_string())
-    }
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let mut equations = Vec::new();
-        for line in contents.lines() {
-            let (raw_result, raw_values) = line.split_once(":").ok_or("Invalid line")?;
-            let result = raw_result.parse::<u64>().map_err(|e| e.to_string())?;
-            let values: VecDeque<_> = raw_values
-                .trim()
-                .split_whitespace()
-                .map(|s| s.parse::<u64>())
-                .try_collect()
-                .map_err(|e| e.to_string())?;
-            equations.push((result, values));
-        }
+        assert_eq!(Day07.solve_a(contents.clone())?, 3749);
+        assert_eq!(Day07.solve_b(contents)?, 11387);
+        Ok(())
+    }
 
-        let mut total_result = 0u64;
+    #[test]
+    fn test_format_solution() -> Result<(), String> {
+        let operators: Vec<_> = ["add", "mul"].into_iter().map(operator).try_collect()?;
+        let equation = &parse_input(String::from("3267: 81 40 27"))?[0];
 
-        for (expected_result, values) in equations {
-            if is_solvable_b(expected_result, values) {
-                total_result += expected_result;
-            }
-        }
+        let witness = solve(equation.result, &equation.values, &operators).unwrap();
 
-        total_result
-            .try_into()
-            .map_err(|e: TryFromIntError| e.to_string())
+        assert_eq!(
+            format_solution(equation, &operators, &witness),
+            "3267: 81 * 40 + 27"
+        );
+        Ok(())
     }
 }
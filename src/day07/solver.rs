@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+/// Binary operator of an equation, evaluated left to right.
+pub trait Operator: Sync {
+    fn symbol(&self) -> &'static str;
+    /// `None` if the result is not representable
+    fn apply(&self, left: u128, right: u128) -> Option<u128>;
+    /// Every `left` with `apply(left, right) == result`
+    fn invert(&self, result: u128, right: u128) -> Inverse;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inverse {
+    None,
+    One(u128),
+    /// Any `left` works, like multiplying by zero into zero
+    Any,
+}
+impl From<Option<u128>> for Inverse {
+    fn from(value: Option<u128>) -> Self {
+        value.map_or(Self::None, Self::One)
+    }
+}
+
+pub struct Add;
+impl Operator for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+    fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        left.checked_add(right)
+    }
+    fn invert(&self, result: u128, right: u128) -> Inverse {
+        result.checked_sub(right).into()
+    }
+}
+
+pub struct Mul;
+impl Operator for Mul {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+    fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        left.checked_mul(right)
+    }
+    fn invert(&self, result: u128, right: u128) -> Inverse {
+        match (result, right) {
+            (0, 0) => Inverse::Any,
+            (_, 0) => Inverse::None,
+            _ if result.is_multiple_of(right) => Inverse::One(result / right),
+            _ => Inverse::None,
+        }
+    }
+}
+
+/// Appends the digits of the right operand to the left one.
+pub struct Concat;
+impl Concat {
+    fn shift(right: u128) -> Option<u128> {
+        10u128.checked_pow(right.checked_ilog10().unwrap_or(0) + 1)
+    }
+}
+impl Operator for Concat {
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+    fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        left.checked_mul(Self::shift(right)?)?.checked_add(right)
+    }
+    fn invert(&self, result: u128, right: u128) -> Inverse {
+        let Some(shift) = Self::shift(right) else {
+            return Inverse::None;
+        };
+        match result.checked_sub(right) {
+            Some(rest) if rest.is_multiple_of(shift) => Inverse::One(rest / shift),
+            _ => Inverse::None,
+        }
+    }
+}
+
+pub struct Sub;
+impl Operator for Sub {
+    fn symbol(&self) -> &'static str {
+        "-"
+    }
+    fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        left.checked_sub(right)
+    }
+    fn invert(&self, result: u128, right: u128) -> Inverse {
+        result.checked_add(right).into()
+    }
+}
+
+pub struct Xor;
+impl Operator for Xor {
+    fn symbol(&self) -> &'static str {
+        "^"
+    }
+    fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        Some(left ^ right)
+    }
+    fn invert(&self, result: u128, right: u128) -> Inverse {
+        Inverse::One(result ^ right)
+    }
+}
+
+pub fn operator(name: &str) -> Result<Box<dyn Operator>, String> {
+    Ok(match name.trim() {
+        "add" | "+" => Box::new(Add),
+        "mul" | "*" => Box::new(Mul),
+        "concat" | "||" => Box::new(Concat),
+        "sub" | "-" => Box::new(Sub),
+        "xor" | "^" => Box::new(Xor),
+        _ => {
+            return Err(format!(
+                "unknown operator {name}, expected add, mul, concat, sub or xor"
+            ))
+        }
+    })
+}
+
+/// Index into `operators` for every gap between two values, so that evaluating the values
+/// left to right gives `result`. Searches from the right: an operator is only tried if it
+/// can be inverted for the current result, which prunes by divisibility for `*` and by
+/// suffix for `||`.
+pub fn solve(result: u128, values: &[u128], operators: &[Box<dyn Operator>]) -> Option<Vec<usize>> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut witness = vec![0; values.len() - 1];
+    search(values, operators, result, &mut witness).then_some(witness)
+}
+
+/// Whether `values` can produce `result`, filling in `witness` for their gaps.
+fn search(
+    values: &[u128],
+    operators: &[Box<dyn Operator>],
+    result: u128,
+    witness: &mut [usize],
+) -> bool {
+    let Some((&last, rest)) = values.split_last() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return last == result;
+    }
+    for (i, operator) in operators.iter().enumerate() {
+        let found = match operator.invert(result, last) {
+            Inverse::None => false,
+            Inverse::One(left) => search(rest, operators, left, &mut witness[..rest.len() - 1]),
+            Inverse::Any => evaluable(rest, operators, &mut witness[..rest.len() - 1]),
+        };
+        if found {
+            witness[rest.len() - 1] = i;
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether any operators make `values` evaluate without leaving the value range.
+fn evaluable(values: &[u128], operators: &[Box<dyn Operator>], witness: &mut [usize]) -> bool {
+    fn evaluate_from(
+        value: u128,
+        values: &[u128],
+        operators: &[Box<dyn Operator>],
+        witness: &mut [usize],
+    ) -> bool {
+        let Some((&next, rest)) = values.split_first() else {
+            return true;
+        };
+        for (i, operator) in operators.iter().enumerate() {
+            if let Some(value) = operator.apply(value, next) {
+                if evaluate_from(value, rest, operators, &mut witness[1..]) {
+                    witness[0] = i;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+    match values.split_first() {
+        None => false,
+        Some((&first, rest)) => evaluate_from(first, rest, operators, witness),
+    }
+}
+
+/// Evaluates `values` left to right with the operators picked by `witness`.
+pub fn evaluate(
+    values: &[u128],
+    operators: &[Box<dyn Operator>],
+    witness: &[usize],
+) -> Option<u128> {
+    let (&first, rest) = values.split_first()?;
+    rest.iter()
+        .zip(witness)
+        .try_fold(first, |value, (&next, &i)| operators[i].apply(value, next))
+}
+
+/// All operator assignments of an equation at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Analysis {
+    /// Number of assignments evaluating to the target
+    pub solutions: u128,
+    /// Smallest and largest value any assignment evaluates to, `None` if none stays in range
+    pub reachable: Option<(u128, u128)>,
+}
+
+/// Same as `Analysis` for the values after some index, given the value so far.
+#[derive(Clone, Copy)]
+struct Reach {
+    solutions: u128,
+    min: u128,
+    max: u128,
+}
+
+pub fn analyse(result: u128, values: &[u128], operators: &[Box<dyn Operator>]) -> Analysis {
+    let Some((&first, _)) = values.split_first() else {
+        return Analysis {
+            solutions: 0,
+            reachable: None,
+        };
+    };
+    let mut memo = HashMap::new();
+    let reach = reach(values, operators, result, 1, first, &mut memo);
+    Analysis {
+        solutions: reach.map_or(0, |reach| reach.solutions),
+        reachable: reach.map(|reach| (reach.min, reach.max)),
+    }
+}
+
+/// Memoised over `(index, value)`, since different prefixes often meet in the same value.
+fn reach(
+    values: &[u128],
+    operators: &[Box<dyn Operator>],
+    result: u128,
+    index: usize,
+    value: u128,
+    memo: &mut HashMap<(usize, u128), Option<Reach>>,
+) -> Option<Reach> {
+    if index == values.len() {
+        return Some(Reach {
+            solutions: (value == result).into(),
+            min: value,
+            max: value,
+        });
+    }
+    if let Some(&known) = memo.get(&(index, value)) {
+        return known;
+    }
+    let reach = operators
+        .iter()
+        .filter_map(|operator| operator.apply(value, values[index]))
+        .filter_map(|next| reach(values, operators, result, index + 1, next, memo))
+        .reduce(|a, b| Reach {
+            solutions: a.solutions + b.solutions,
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        });
+    memo.insert((index, value), reach);
+    reach
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operators(names: &str) -> Vec<Box<dyn Operator>> {
+        names
+            .split(',')
+            .map(|name| operator(name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_witness() {
+        let operators = operators("add,mul,concat");
+
+        let witness = solve(7290, &[6, 8, 6, 15], &operators);
+
+        assert_eq!(witness, Some(vec![1, 2, 1]));
+        assert_eq!(solve(7291, &[6, 8, 6, 15], &operators), None);
+    }
+
+    #[test]
+    fn test_user_defined_operators() {
+        let operators = operators("sub,xor");
+
+        // (10 - 3) ^ 5 = 2
+        let witness = solve(2, &[10, 3, 5], &operators).unwrap();
+
+        assert_eq!(evaluate(&[10, 3, 5], &operators, &witness), Some(2));
+        // 3 - 10 leaves the value range
+        assert_eq!(solve(7 ^ 5, &[3, 10, 5], &operators[..1]), None);
+    }
+
+    #[test]
+    fn test_multiplying_by_zero() {
+        let operators = operators("sub,mul");
+
+        let witness = solve(0, &[1, 2, 0], &operators).unwrap();
+
+        assert_eq!(evaluate(&[1, 2, 0], &operators, &witness), Some(0));
+    }
+
+    #[test]
+    fn test_analyse() {
+        let operators = operators("add,mul,concat");
+
+        assert_eq!(
+            analyse(7290, &[6, 8, 6, 15], &operators),
+            Analysis {
+                solutions: 1,
+                reachable: Some((35, 68615))
+            }
+        );
+        // 1 * 1 + 1 and 1 + 1 * 1
+        assert_eq!(analyse(2, &[1, 1, 1], &operators).solutions, 2);
+    }
+
+    #[test]
+    fn test_beyond_u64() {
+        let operators = operators("add,mul,concat");
+        let values = [u64::MAX.into(), 10, 1];
+        let result = u128::from(u64::MAX) * 1000 + 101;
+
+        let witness = solve(result, &values, &operators).unwrap();
+
+        assert_eq!(witness, vec![2, 2]);
+        assert_eq!(analyse(result, &values, &operators).solutions, 1);
+        // concatenating beyond u128 is not a solution, not an overflow
+        assert_eq!(solve(u128::MAX, &[u128::MAX, 1], &operators[2..]), None);
+        assert_eq!(analyse(0, &[u128::MAX, 1], &operators[2..]).reachable, None);
+    }
+}
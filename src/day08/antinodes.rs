@@ -0,0 +1,209 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+
+use crate::aoc_util::gcd;
+
+pub type Position = (isize, isize);
+
+/// Where a pair of antennas of the same frequency creates antinodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// `k` times the distance between the antennas beyond either of them, `0` being the
+    /// antennas themselves
+    Harmonic(usize),
+    /// All harmonics from `from` up to but excluding `to`, or up to the edge of the map
+    Harmonics { from: usize, to: Option<usize> },
+    /// Every grid point on the line through both antennas, including the ones between them
+    Collinear,
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// `k`, `from..to`, `from..` or `collinear`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |k: &str| k.trim().parse::<usize>().map_err(|e| format!("{s}: {e}"));
+        if s == "collinear" {
+            return Ok(Self::Collinear);
+        }
+        match s.split_once("..") {
+            None => Ok(Self::Harmonic(parse(s)?)),
+            Some((from, "")) => Ok(Self::Harmonics {
+                from: parse(from)?,
+                to: None,
+            }),
+            Some((from, to)) => Ok(Self::Harmonics {
+                from: parse(from)?,
+                to: Some(parse(to)?),
+            }),
+        }
+    }
+}
+
+pub struct Map {
+    height: isize,
+    width: isize,
+    /// Antenna positions by frequency
+    antennas: BTreeMap<char, Vec<Position>>,
+}
+
+impl FromStr for Map {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut antennas: BTreeMap<char, Vec<Position>> = BTreeMap::new();
+        let mut width = None;
+        let mut height = 0isize;
+        for (y, line) in s.lines().enumerate() {
+            width = Some(line.len() as isize);
+            for (x, c) in line.chars().enumerate() {
+                if c == '.' || c == '#' {
+                    continue;
+                }
+                antennas
+                    .entry(c)
+                    .or_default()
+                    .push((y as isize, x as isize));
+            }
+            height += 1;
+        }
+        let width = width.ok_or("empty map")?;
+        Ok(Map {
+            height,
+            width,
+            antennas,
+        })
+    }
+}
+
+impl Map {
+    fn contains(&self, (y, x): Position) -> bool {
+        y >= 0 && y < self.height && x >= 0 && x < self.width
+    }
+
+    /// Points `from + k * step` for `k` in `ks` as long as they are on the map
+    fn walk<'a>(
+        &'a self,
+        (y, x): Position,
+        (dy, dx): Position,
+        ks: impl Iterator<Item = usize> + 'a,
+    ) -> impl Iterator<Item = Position> + 'a {
+        ks.map_while(move |k| {
+            let k = isize::try_from(k).ok()?;
+            Some((
+                y.checked_add(k.checked_mul(dy)?)?,
+                x.checked_add(k.checked_mul(dx)?)?,
+            ))
+        })
+        .take_while(|&position| self.contains(position))
+    }
+
+    fn pair_antinodes(&self, a: Position, b: Position, rule: Rule) -> Vec<Position> {
+        let (dy, dx) = (b.0 - a.0, b.1 - a.1);
+        let (from, to) = match rule {
+            Rule::Harmonic(k) => (k, Some(k + 1)),
+            Rule::Harmonics { from, to } => (from, to),
+            Rule::Collinear => {
+                let divisor = gcd(dy, dx).abs();
+                let step = (dy / divisor, dx / divisor);
+                let backwards = (-step.0, -step.1);
+                return self
+                    .walk(a, step, 0..)
+                    .chain(self.walk(a, backwards, 1..))
+                    .collect();
+            }
+        };
+        let ks = from..to.unwrap_or(usize::MAX);
+        self.walk(b, (dy, dx), ks.clone())
+            .chain(self.walk(a, (-dy, -dx), ks))
+            .collect()
+    }
+
+    /// Antinodes of every frequency on its own
+    pub fn antinodes_by_frequency(&self, rule: Rule) -> BTreeMap<char, HashSet<Position>> {
+        self.antennas
+            .iter()
+            .map(|(&frequency, positions)| {
+                let antinodes = positions
+                    .iter()
+                    .tuple_combinations()
+                    .flat_map(|(&a, &b)| self.pair_antinodes(a, b, rule))
+                    .collect();
+                (frequency, antinodes)
+            })
+            .collect()
+    }
+
+    pub fn antinodes(&self, rule: Rule) -> HashSet<Position> {
+        self.antinodes_by_frequency(rule)
+            .into_values()
+            .flatten()
+            .collect()
+    }
+
+    /// The map with `#` for antinodes, like the puzzle draws them. Antennas stay visible.
+    pub fn render(&self, antinodes: &HashSet<Position>) -> String {
+        let mut rows = vec![vec!['.'; self.width as usize]; self.height as usize];
+        for &(y, x) in antinodes {
+            rows[y as usize][x as usize] = '#';
+        }
+        for (&frequency, positions) in &self.antennas {
+            for &(y, x) in positions {
+                rows[y as usize][x as usize] = frequency;
+            }
+        }
+        rows.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() -> Result<(), String> {
+        let map = Map::from_str("a....\n.....\n..a..\n.....\n.....\n")?;
+
+        assert_eq!(map.antinodes(Rule::Harmonic(1)), HashSet::from([(4, 4)]));
+        assert_eq!(map.antinodes(Rule::Harmonic(2)), HashSet::new());
+        assert_eq!(map.antinodes(Rule::from_str("0..")?).len(), 3);
+
+        let map = Map::from_str("a....\n.....\n....a\n.....\n.....\n")?;
+
+        assert_eq!(map.antinodes(Rule::from_str("0..")?).len(), 2);
+        assert_eq!(
+            map.antinodes(Rule::Collinear),
+            HashSet::from([(0, 0), (1, 2), (2, 4)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<(), String> {
+        // from the puzzle description, antinodes are ignored when parsing
+        let drawn = "\
+            ..........\n\
+            ...#......\n\
+            ..........\n\
+            ....a.....\n\
+            ..........\n\
+            .....a....\n\
+            ..........\n\
+            ......#...\n\
+            ..........\n\
+            ..........\n";
+        let map = Map::from_str(drawn)?;
+
+        let by_frequency = map.antinodes_by_frequency(Rule::Harmonic(1));
+
+        assert_eq!(by_frequency[&'a'], HashSet::from([(1, 3), (7, 6)]));
+        assert_eq!(map.render(&by_frequency[&'a']), drawn);
+        Ok(())
+    }
+}
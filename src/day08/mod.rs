@@ -1,136 +1,75 @@
-use std::collections::HashSet;
+use std::str::FromStr;
 
-use itertools::Itertools;
+use antinodes::{Map, Rule};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod antinodes;
 
 pub struct Day08;
 
-type Position = (isize, isize);
+impl AocTask for Day08 {
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let rule = match part {
+            TaskPart::A => "1",
+            TaskPart::B => "0..",
+        };
+        vec![
+            // harmonic `k`, harmonics `from..to` or `from..`, or `collinear`
+            ParamSpec::new("rule", ParamKind::Text, rule),
+            // prints the number of antinodes of every frequency
+            ParamSpec::new("per_frequency", ParamKind::Flag, "false"),
+            // prints the map with all antinodes
+            ParamSpec::new("render", ParamKind::Flag, "false"),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_antinodes(contents, params)
+    }
 
-struct Antenna {
-    frequency: char,
-    position: Position,
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_antinodes(contents, params)
+    }
 }
 
-fn get_antinodes(
-    (height, width): (isize, isize),
-    (ay, ax): Position,
-    (by, bx): Position,
-) -> Vec<Position> {
-    let dy = (by as isize) - (ay as isize);
-    let dx = (bx as isize) - (ax as isize);
-    let mut antinodes = Vec::new();
-    {
-        let y = (by as isize) + dy;
-        let x = (bx as isize) + dx;
-        if y >= 0 && y < height && x >= 0 && x < width {
-            antinodes.push((y, x));
+fn count_antinodes(contents: String, params: &Params) -> AocResult {
+    let map = Map::from_str(&contents)?;
+    let rule = Rule::from_str(params.text("rule")?)?;
+
+    if params.flag("per_frequency")? {
+        for (frequency, antinodes) in map.antinodes_by_frequency(rule) {
+            println!("{frequency}: {} antinodes", antinodes.len());
         }
     }
-    {
-        let y = (ay as isize) - dy;
-        let x = (ax as isize) - dx;
-        if y >= 0 && y < height && x >= 0 && x < width {
-            antinodes.push((y, x));
-        }
+    let antinodes = map.antinodes(rule);
+    if params.flag("render")? {
+        print!("{}", map.render(&antinodes));
     }
-    return antinodes;
+    Ok(antinodes.len() as i64)
 }
 
-impl AocTask for Day08 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut antennas = Vec::new();
-        let mut width = None;
-        let mut height = 0isize;
-        for (y, line) in contents.lines().enumerate() {
-            width = Some(line.len() as isize);
-            for (x, c) in line.chars().enumerate() {
-                if c == '.' {
-                    continue;
-                }
-                antennas.push(Antenna {
-                    frequency: c,
-                    position: (y as isize, x as isize),
-                });
-            }
-            height += 1;
-        }
-        let width = width.ok_or("empty map")?;
-        let antennas_by_frequency = antennas.iter().into_group_map_by(|&a| a.frequency);
-        let mut antinodes = HashSet::new();
-        for (_, antennas) in antennas_by_frequency {
-            for (a, b) in antennas.iter().tuple_combinations() {
-                antinodes.extend(get_antinodes((height, width), a.position, b.position));
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
 
-        Ok(antinodes.len() as i64)
-    }
+    use crate::aoc_core::read_file;
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let mut antennas = Vec::new();
-        let mut width = None;
-        let mut height = 0isize;
-        for (y, line) in contents.lines().enumerate() {
-            width = Some(line.len() as isize);
-            for (x, c) in line.chars().enumerate() {
-                if c == '.' {
-                    continue;
-                }
-                antennas.push(Antenna {
-                    frequency: c,
-                    position: (y as isize, x as isize),
-                });
-            }
-            height += 1;
-        }
-        let width = width.ok_or("empty map")?;
-        let antennas_by_frequency = antennas.iter().into_group_map_by(|&a| a.frequency);
-        let mut antinodes = HashSet::new();
-        for (_, antennas) in antennas_by_frequency {
-            for (a, b) in antennas.iter().tuple_combinations() {
-                antinodes.extend(get_antinodes_b((height, width), a.position, b.position));
-            }
-        }
+    use super::*;
 
-        Ok(antinodes.len() as i64)
-    }
-}
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
 
-fn get_antinodes_b(
-    (height, width): (isize, isize),
-    (ay, ax): Position,
-    (by, bx): Position,
-) -> Vec<Position> {
-    let dy = (by as isize) - (ay as isize);
-    let dx = (bx as isize) - (ax as isize);
-    let mut antinodes = vec![(ay, ax), (by, bx)];
-    {
-        let mut y = by;
-        let mut x = bx;
-        loop {
-            y += dy;
-            x += dx;
-            if y >= 0 && y < height && x >= 0 && x < width {
-                antinodes.push((y, x));
-            } else {
-                break;
-            }
-        }
-    }
-    {
-        let mut y = ay;
-        let mut x = ax;
-        loop {
-            y -= dy;
-            x -= dx;
-            if y >= 0 && y < height && x >= 0 && x < width {
-                antinodes.push((y, x));
-            } else {
-                break;
-            }
-        }
+        assert_eq!(Day08.solve_a(contents.clone())?, 14);
+        assert_eq!(Day08.solve_b(contents)?, 34);
+        assert_eq!(
+            Day08.solve_b(read_file(Path::new(file!()), "sample_2.txt"))?,
+            9
+        );
+        Ok(())
     }
-    return antinodes;
 }
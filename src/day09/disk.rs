@@ -0,0 +1,416 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+    fmt::Display,
+};
+
+use super::render::Layout;
+
+/// Files at absolute block positions. A file is a list of `(position, size)` extents, more
+/// than one once it got fragmented by block-level compaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    size: usize,
+    files: Vec<Vec<(usize, usize)>>,
+}
+
+impl Disk {
+    pub fn from_disk_map(disk_map: &[(usize, usize)]) -> Disk {
+        let mut files = Vec::with_capacity(disk_map.len());
+        let mut position = 0;
+        for &(file, free) in disk_map {
+            files.push(vec![(position, file)]);
+            position += file + free;
+        }
+        Disk {
+            size: position,
+            files,
+        }
+    }
+
+    /// `(id, position, size)` of every extent
+    pub fn extents(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.files.iter().enumerate().flat_map(|(id, extents)| {
+            extents
+                .iter()
+                .map(move |&(position, size)| (id, position, size))
+        })
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout::from_extents(self.extents(), self.size)
+    }
+
+    /// Too large checksums are an error instead of overflowing.
+    pub fn checksum(&self) -> Result<i64, String> {
+        let mut checksum = 0u128;
+        for (id, position, size) in self.extents() {
+            let (position, size) = (position as u128, size as u128);
+            // sum of the positions position..position + size
+            let positions = size * position + size * size.saturating_sub(1) / 2;
+            checksum += id as u128 * positions;
+        }
+        i64::try_from(checksum)
+            .map_err(|_| format!("checksum {checksum} does not fit into the result"))
+    }
+
+    /// Free spans as `(position, size)`, ordered by position
+    fn free_spans(&self) -> BTreeMap<usize, usize> {
+        let mut used = self
+            .extents()
+            .filter(|&(_, _, size)| size > 0)
+            .map(|(_, position, size)| (position, size))
+            .collect::<Vec<_>>();
+        used.sort();
+        let mut spans = BTreeMap::new();
+        let mut position = 0;
+        for (start, size) in used {
+            if start > position {
+                spans.insert(position, start - position);
+            }
+            position = start + size;
+        }
+        if self.size > position {
+            spans.insert(position, self.size - position);
+        }
+        spans
+    }
+
+    pub fn fragmentation(&self) -> Fragmentation {
+        let end = self
+            .extents()
+            .filter(|&(_, _, size)| size > 0)
+            .map(|(_, position, size)| position + size)
+            .max()
+            .unwrap_or(0);
+        let spans = self.free_spans();
+        let gaps = spans.range(..end);
+        Fragmentation {
+            fragmented_files: self.files.iter().filter(|e| e.len() > 1).count(),
+            gaps: gaps.clone().count(),
+            gap_blocks: gaps.map(|(_, size)| size).sum(),
+            largest_free_span: spans.values().copied().max().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragmentation {
+    /// Files split into more than one extent
+    pub fragmented_files: usize,
+    /// Free spans left of the last used block
+    pub gaps: usize,
+    /// Free blocks left of the last used block
+    pub gap_blocks: usize,
+    pub largest_free_span: usize,
+}
+
+/// Rearranges the files of a disk and returns the number of moves it took.
+pub trait CompactionStrategy {
+    fn name(&self) -> &'static str;
+    fn compact(&self, disk: &mut Disk) -> usize;
+}
+
+/// Outcome of compacting a disk with one strategy.
+pub struct Compaction {
+    pub strategy: &'static str,
+    pub checksum: i64,
+    pub moves: usize,
+    pub fragmentation: Fragmentation,
+}
+impl Display for Compaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Fragmentation {
+            fragmented_files,
+            gaps,
+            gap_blocks,
+            largest_free_span,
+        } = self.fragmentation;
+        write!(
+            f,
+            "{:<14} checksum {:>15}  moves {:>6}  fragmented files {:>5}  gaps {:>5} ({} blocks)  largest free span {}",
+            self.strategy, self.checksum, self.moves, fragmented_files, gaps, gap_blocks, largest_free_span
+        )
+    }
+}
+
+pub fn compact(disk: &mut Disk, strategy: &dyn CompactionStrategy) -> Result<Compaction, String> {
+    let moves = strategy.compact(disk);
+    Ok(Compaction {
+        strategy: strategy.name(),
+        checksum: disk.checksum()?,
+        moves,
+        fragmentation: disk.fragmentation(),
+    })
+}
+
+pub fn strategies() -> Vec<Box<dyn CompactionStrategy>> {
+    vec![
+        Box::new(BlockLevel),
+        Box::new(FirstFit),
+        Box::new(WholeFile(Fit::Best)),
+        Box::new(WholeFile(Fit::Worst)),
+        Box::new(SmallestFirst),
+    ]
+}
+
+pub fn strategy(name: &str) -> Result<Box<dyn CompactionStrategy>, String> {
+    strategies()
+        .into_iter()
+        .find(|strategy| strategy.name() == name)
+        .ok_or_else(|| {
+            let names = strategies().iter().map(|s| s.name()).collect::<Vec<_>>();
+            format!(
+                "unknown strategy {name}, expected one of {}",
+                names.join(", ")
+            )
+        })
+}
+
+/// Moves single blocks from the end into the leftmost free block until there are no gaps,
+/// counting one move per block.
+pub struct BlockLevel;
+impl CompactionStrategy for BlockLevel {
+    fn name(&self) -> &'static str {
+        "block"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut blocks = vec![None; disk.size];
+        for (id, position, size) in disk.extents() {
+            blocks[position..position + size].fill(Some(id));
+        }
+        let mut moves = 0;
+        let (mut free, mut used) = (0, blocks.len());
+        loop {
+            while free < blocks.len() && blocks[free].is_some() {
+                free += 1;
+            }
+            while used > 0 && blocks[used - 1].is_none() {
+                used -= 1;
+            }
+            if free >= used {
+                break;
+            }
+            blocks.swap(free, used - 1);
+            moves += 1;
+        }
+
+        for extents in &mut disk.files {
+            extents.clear();
+        }
+        for (position, id) in blocks.into_iter().enumerate() {
+            let Some(id) = id else {
+                continue;
+            };
+            match disk.files[id].last_mut() {
+                Some((start, size)) if *start + *size == position => *size += 1,
+                _ => disk.files[id].push((position, 1)),
+            }
+        }
+        moves
+    }
+}
+
+/// Moves every file once, by decreasing id, into the leftmost free span left of it that is
+/// large enough. Free spans are kept in one min-heap of positions per span size, so the
+/// leftmost fitting span is the smallest head among the heaps of large enough sizes. The
+/// space a file leaves behind is not reused, as on the puzzle's disk maps it is right of
+/// every file still to move.
+pub struct FirstFit;
+impl CompactionStrategy for FirstFit {
+    fn name(&self) -> &'static str {
+        "first_fit"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let spans = disk.free_spans();
+        let largest = spans.values().copied().max().unwrap_or(0);
+        let mut free_spans = vec![BinaryHeap::new(); largest + 1];
+        for (position, size) in spans {
+            free_spans[size].push(Reverse(position));
+        }
+
+        let mut moves = 0;
+        for extents in disk.files.iter_mut().rev() {
+            for (file_position, file) in extents.iter_mut() {
+                if *file == 0 {
+                    continue;
+                }
+                let leftmost = (*file..free_spans.len())
+                    .filter_map(|size| Some((free_spans[size].peek()?.0, size)))
+                    .min();
+                let Some((span_position, size)) = leftmost else {
+                    continue;
+                };
+                if span_position >= *file_position {
+                    continue;
+                }
+                free_spans[size].pop();
+                if size > *file {
+                    free_spans[size - *file].push(Reverse(span_position + *file));
+                }
+                *file_position = span_position;
+                moves += 1;
+            }
+        }
+        moves
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// Leftmost span
+    First,
+    /// Smallest span, the leftmost of those
+    Best,
+    /// Largest span, the leftmost of those
+    Worst,
+}
+
+/// Moves every file once, by decreasing id, into a free span left of it chosen by the fit.
+/// The space a file leaves behind becomes free for the files moved after it.
+pub struct WholeFile(pub Fit);
+impl CompactionStrategy for WholeFile {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            Fit::First => "first_fit_reuse",
+            Fit::Best => "best_fit",
+            Fit::Worst => "worst_fit",
+        }
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let order = (0..disk.files.len()).rev().collect::<Vec<_>>();
+        move_files(disk, &order, self.0)
+    }
+}
+
+/// Moves the smallest files first, ties by decreasing id, each into the leftmost free span
+/// left of it.
+pub struct SmallestFirst;
+impl CompactionStrategy for SmallestFirst {
+    fn name(&self) -> &'static str {
+        "smallest_first"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut order = (0..disk.files.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&id| {
+            let size: usize = disk.files[id].iter().map(|(_, size)| size).sum();
+            (size, Reverse(id))
+        });
+        move_files(disk, &order, Fit::First)
+    }
+}
+
+fn move_files(disk: &mut Disk, order: &[usize], fit: Fit) -> usize {
+    let mut spans = disk.free_spans();
+    let mut moves = 0;
+    for &id in order {
+        for extent in disk.files[id].iter_mut() {
+            let (file_position, file) = *extent;
+            if file == 0 {
+                continue;
+            }
+            let candidates = spans
+                .range(..file_position)
+                .filter(|(_, &size)| size >= file)
+                .map(|(&position, &size)| (position, size));
+            let chosen = match fit {
+                Fit::First => candidates.min_by_key(|&(position, _)| position),
+                Fit::Best => candidates.min_by_key(|&(position, size)| (size, position)),
+                Fit::Worst => candidates.min_by_key(|&(position, size)| (Reverse(size), position)),
+            };
+            let Some((position, size)) = chosen else {
+                continue;
+            };
+            spans.remove(&position);
+            if size > file {
+                spans.insert(position + file, size - file);
+            }
+            free(&mut spans, file_position, file);
+            *extent = (position, file);
+            moves += 1;
+        }
+    }
+    moves
+}
+
+/// Adds a free span, merging it with adjacent ones.
+fn free(spans: &mut BTreeMap<usize, usize>, mut position: usize, mut size: usize) {
+    if let Some((&previous, &previous_size)) = spans.range(..position).next_back() {
+        if previous + previous_size == position {
+            spans.remove(&previous);
+            position = previous;
+            size += previous_size;
+        }
+    }
+    if let Some(next_size) = spans.remove(&(position + size)) {
+        size += next_size;
+    }
+    spans.insert(position, size);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Disk {
+        let digits = "2333133121414131402"
+            .chars()
+            .map(|c| c.to_digit(10).unwrap() as usize);
+        let digits = digits.collect::<Vec<_>>();
+        let disk_map = digits
+            .chunks(2)
+            .map(|c| (c[0], c.get(1).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        Disk::from_disk_map(&disk_map)
+    }
+
+    #[test]
+    fn test_puzzle_strategies() -> Result<(), String> {
+        let block = compact(&mut sample(), &BlockLevel)?;
+        let first_fit = compact(&mut sample(), &FirstFit)?;
+
+        assert_eq!(block.checksum, 1928);
+        assert_eq!(block.fragmentation.gaps, 0);
+        assert_eq!(first_fit.checksum, 2858);
+        assert_eq!(first_fit.moves, 4);
+        assert_eq!(
+            compact(&mut sample(), &WholeFile(Fit::First))?.checksum,
+            first_fit.checksum
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fits() {
+        // gaps of 3 and 2 blocks, then a file of 2 blocks
+        let disk = Disk::from_disk_map(&[(1, 3), (1, 2), (2, 0)]);
+
+        let mut best = disk.clone();
+        WholeFile(Fit::Best).compact(&mut best);
+        let mut worst = disk.clone();
+        WholeFile(Fit::Worst).compact(&mut worst);
+
+        assert_eq!(best.files[2], &[(5, 2)]);
+        assert_eq!(worst.files[2], &[(1, 2)]);
+    }
+
+    #[test]
+    fn test_smallest_first_reuses_freed_space() {
+        // 0.1...22, file 1 moves first and leaves a gap large enough for file 2
+        let disk = Disk::from_disk_map(&[(1, 1), (1, 3), (2, 0)]);
+
+        let mut smallest_first = disk.clone();
+        let moves = SmallestFirst.compact(&mut smallest_first);
+        let mut first_fit = disk.clone();
+        FirstFit.compact(&mut first_fit);
+
+        assert_eq!(moves, 2);
+        assert_eq!(smallest_first.files[2], &[(2, 2)]);
+        assert_eq!(smallest_first.fragmentation().gaps, 0);
+        assert_eq!(first_fit.fragmentation().gaps, 1);
+    }
+}
@@ -1,15 +1,22 @@
-use std::num::TryFromIntError;
+use std::{num::TryFromIntError, str::FromStr};
 
-use itertools::Itertools;
+use disk::{compact, strategies, strategy, Disk};
+use render::{Layout, RenderFormat};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod disk;
+mod render;
 
 pub struct Day09;
 
 fn parse_input(contents: String) -> Result<Vec<(usize, usize)>, String> {
     let mut file_block = None;
     let mut disk_map = Vec::new();
-    for c in contents.chars() {
+    for c in contents.trim_end().chars() {
         let i: usize = c
             .to_digit(10)
             .ok_or("Invalid char")?
@@ -28,7 +35,8 @@ fn parse_input(contents: String) -> Result<Vec<(usize, usize)>, String> {
     Ok(disk_map)
 }
 
-fn compact_disk(disk_map: &mut Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+#[cfg(test)]
+fn compact_disk(disk_map: &mut [(usize, usize)]) -> Vec<(usize, usize)> {
     let mut i = 0;
     let mut j = disk_map.len() - 1;
     let mut compacted_disk_map = Vec::new();
@@ -71,12 +79,11 @@ fn compact_disk(disk_map: &mut Vec<(usize, usize)>) -> Vec<(usize, usize)> {
     if j == i && !already_added_i {
         compacted_disk_map.push((i, disk_map[i].0));
     }
-    return compacted_disk_map;
+    compacted_disk_map
 }
 
-fn compact_disk_without_fragmentation(
-    disk_map: &Vec<(usize, usize)>,
-) -> Vec<(usize, usize, usize)> {
+#[cfg(test)]
+fn compact_disk_without_fragmentation(disk_map: &[(usize, usize)]) -> Vec<(usize, usize, usize)> {
     let mut disk_map =
         disk_map
             .iter()
@@ -86,7 +93,7 @@ fn compact_disk_without_fragmentation(
                 map
             });
     fn find_gap(
-        disk_map: &Vec<(usize, usize, usize)>,
+        disk_map: &[(usize, usize, usize)],
         min_size: usize,
         to: usize,
     ) -> Option<(usize, usize)> {
@@ -117,7 +124,7 @@ fn compact_disk_without_fragmentation(
         last_id = Some(id);
         if let Some((index, remaining_free)) = find_gap(&disk_map, file, i) {
             let old: &mut _ = disk_map.get_mut(index).unwrap();
-            *old = ((*old).0, (*old).1, 0);
+            *old = (old.0, old.1, 0);
             disk_map[i] = (0, 0, file + free);
             disk_map.insert(index + 1, (id, file, remaining_free));
             i += 1;
@@ -127,9 +134,10 @@ fn compact_disk_without_fragmentation(
         }
         i -= 1;
     }
-    return disk_map;
+    disk_map
 }
 
+#[cfg(test)]
 fn calculate_checksum<T: IntoIterator<Item = (usize, usize)>>(disk: T) -> i64 {
     let mut checksum = 0i64;
     let mut position = 0i64;
@@ -142,6 +150,7 @@ fn calculate_checksum<T: IntoIterator<Item = (usize, usize)>>(disk: T) -> i64 {
     checksum
 }
 
+#[cfg(test)]
 fn calculate_checksum_b<T: IntoIterator<Item = (usize, usize, usize)>>(disk: T) -> i64 {
     let mut checksum = 0i64;
     let mut position = 0i64;
@@ -156,45 +165,62 @@ fn calculate_checksum_b<T: IntoIterator<Item = (usize, usize, usize)>>(disk: T)
 }
 
 impl AocTask for Day09 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut disk_map = parse_input(contents)?;
-        let compacted_disk_map = compact_disk(&mut disk_map);
-
-        let checksum = calculate_checksum(compacted_disk_map);
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let strategy = match part {
+            TaskPart::A => "block",
+            TaskPart::B => "first_fit",
+        };
+        vec![
+            // block, first_fit, best_fit, worst_fit or smallest_first
+            ParamSpec::new("strategy", ParamKind::Text, strategy),
+            // reports checksum, moves and fragmentation of every strategy
+            ParamSpec::new("compare", ParamKind::Flag, "false"),
+            // prints the disk before and after compacting as `runs`, `csv` or `wrapped`
+            ParamSpec::new("render", ParamKind::Text, ""),
+            // blocks per row of the wrapped view
+            ParamSpec::new("render_width", ParamKind::UInt, "60"),
+        ]
+    }
 
-        Ok(checksum)
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        solve(contents, params)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let disk_map = parse_input(contents)?;
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        solve(contents, params)
+    }
+}
 
-        let compacted_disk_map = compact_disk_without_fragmentation(&disk_map);
+fn solve(contents: String, params: &Params) -> AocResult {
+    let disk = Disk::from_disk_map(&parse_input(contents)?);
+    let strategy = strategy(params.text("strategy")?)?;
 
-        let checksum = calculate_checksum_b(compacted_disk_map);
-        Ok(checksum)
+    if params.flag("compare")? {
+        for strategy in strategies() {
+            println!("{}", compact(&mut disk.clone(), strategy.as_ref())?);
+        }
     }
+
+    let mut compacted = disk.clone();
+    let compaction = compact(&mut compacted, strategy.as_ref())?;
+    print_layouts(params, &disk.layout(), &compacted.layout())?;
+    Ok(compaction.checksum)
 }
-#[allow(dead_code)]
-fn print_disk(disk: &Vec<(usize, usize, usize)>) {
-    println!(
-        "{}",
-        disk.iter()
-            .map(|&(id, span, free)| id.to_string().repeat(span as usize) + &".".repeat(free))
-            .join("")
-    )
-}
-#[allow(dead_code)]
-fn print_simple_disk(disk: &Vec<(usize, usize)>) {
-    print_disk(
-        &disk
-            .iter()
-            .enumerate()
-            .map(|(id, &(span, free))| (id, span, free))
-            .collect::<Vec<_>>(),
-    );
+
+fn print_layouts(params: &Params, before: &Layout, after: &Layout) -> Result<(), String> {
+    let format = params.text("render")?;
+    if format.is_empty() {
+        return Ok(());
+    }
+    let format = RenderFormat::from_str(format)?;
+    let width = params.uint("render_width")? as usize;
+    println!("before:\n{}", before.render(format, width));
+    println!("after:\n{}", after.render(format, width));
+    Ok(())
 }
 
 #[cfg(test)]
+#[allow(clippy::identity_op)]
 mod test_a {
     use super::*;
 
@@ -253,6 +279,7 @@ mod test_a {
     }
 }
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test_b {
     use super::*;
 
@@ -273,3 +300,67 @@ mod test_b {
         assert_eq!(actual, expected);
     }
 }
+
+#[cfg(test)]
+mod test_strategies {
+    use std::path::Path;
+
+    use crate::{aoc_core::read_file, aoc_util::Random};
+
+    use super::{
+        disk::{BlockLevel, FirstFit},
+        *,
+    };
+
+    /// Deterministic pseudo random disk map, files are never empty
+    fn random_disk_map(n_digits: usize, seed: u64) -> String {
+        let mut random = Random::new(seed);
+        (0..n_digits)
+            .map(|i| {
+                let digit = random.below(10) as u32;
+                let digit = if i % 2 == 0 { digit.max(1) } else { digit };
+                char::from_digit(digit, 10).unwrap()
+            })
+            .collect()
+    }
+
+    fn checksum_b(disk_map: &[(usize, usize)]) -> i64 {
+        calculate_checksum_b(compact_disk_without_fragmentation(disk_map))
+    }
+
+    #[test]
+    fn test_matches_original_compactions() -> Result<(), String> {
+        let mut inputs = vec![
+            String::from("2333133121414131402"),
+            read_file(Path::new(file!()), "input.txt"),
+        ];
+        inputs.extend((1..20).map(|seed| random_disk_map(101 + seed as usize * 37, seed)));
+        for contents in inputs {
+            let mut disk_map = parse_input(contents.clone())?;
+            let disk = Disk::from_disk_map(&disk_map);
+
+            assert_eq!(
+                compact(&mut disk.clone(), &FirstFit)?.checksum,
+                checksum_b(&disk_map),
+                "{contents}"
+            );
+            assert_eq!(
+                compact(&mut disk.clone(), &BlockLevel)?.checksum,
+                calculate_checksum(compact_disk(&mut disk_map)),
+                "{contents}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_millions_of_digits() -> Result<(), String> {
+        let mut disk = Disk::from_disk_map(&parse_input(random_disk_map(2_000_001, 42))?);
+
+        let compaction = compact(&mut disk, &FirstFit)?;
+
+        assert_eq!(disk.extents().count(), 1_000_001);
+        assert!(compaction.checksum > 0);
+        Ok(())
+    }
+}
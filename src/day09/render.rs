@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fmt::Write, str::FromStr};
+
+/// Symbols for the files of one row of the wrapped view
+const SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    /// One `idxlength` entry per run of blocks, `.` for free space
+    Runs,
+    /// `position,file_id` for every block holding a file
+    Csv,
+    /// One char per block wrapped into rows, every row with a legend of its file ids
+    Wrapped,
+}
+impl FromStr for RenderFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "runs" => Ok(Self::Runs),
+            "csv" => Ok(Self::Csv),
+            "wrapped" => Ok(Self::Wrapped),
+            _ => Err(format!(
+                "invalid render format: {s}, expected runs, csv or wrapped"
+            )),
+        }
+    }
+}
+
+/// Blocks of a disk as runs of the same file id, `None` being free space.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Layout {
+    runs: Vec<(Option<usize>, usize)>,
+}
+
+impl Layout {
+    /// Layout of `(id, position, size)` file extents, free space up to `size`.
+    pub fn from_extents(
+        extents: impl IntoIterator<Item = (usize, usize, usize)>,
+        size: usize,
+    ) -> Layout {
+        let mut by_position = extents.into_iter().collect::<Vec<_>>();
+        by_position.sort_by_key(|&(_, position, _)| position);
+        let mut layout = Layout { runs: Vec::new() };
+        for (id, position, length) in by_position {
+            layout.push(None, position.saturating_sub(layout.size()));
+            layout.push(Some(id), length);
+        }
+        layout.push(None, size.saturating_sub(layout.size()));
+        layout
+    }
+
+    /// Total number of blocks
+    pub fn size(&self) -> usize {
+        self.runs.iter().map(|(_, length)| length).sum()
+    }
+
+    fn push(&mut self, id: Option<usize>, length: usize) {
+        if length == 0 {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some((last, last_length)) if *last == id => *last_length += length,
+            _ => self.runs.push((id, length)),
+        }
+    }
+
+    fn blocks(&self) -> impl Iterator<Item = Option<usize>> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|&(id, length)| std::iter::repeat_n(id, length))
+    }
+
+    pub fn render(&self, format: RenderFormat, width: usize) -> String {
+        match format {
+            RenderFormat::Runs => self.run_length(),
+            RenderFormat::Csv => self.csv(),
+            RenderFormat::Wrapped => self.wrapped(width),
+        }
+    }
+
+    fn run_length(&self) -> String {
+        let runs = self.runs.iter().map(|&(id, length)| match id {
+            Some(id) => format!("{id}x{length}"),
+            None => format!(".x{length}"),
+        });
+        runs.collect::<Vec<_>>().join(" ") + "\n"
+    }
+
+    fn csv(&self) -> String {
+        let mut csv = String::from("position,file_id\n");
+        for (position, id) in self.blocks().enumerate() {
+            if let Some(id) = id {
+                writeln!(csv, "{position},{id}").unwrap();
+            }
+        }
+        csv
+    }
+
+    /// Symbols are assigned per row, so ids of any size fit into a single char. Rows are at
+    /// most as wide as there are symbols.
+    fn wrapped(&self, width: usize) -> String {
+        let width = width.clamp(1, SYMBOLS.len());
+        let blocks = self.blocks().collect::<Vec<_>>();
+        let mut result = String::new();
+        for row in blocks.chunks(width) {
+            let mut symbols = HashMap::new();
+            let mut legend = Vec::new();
+            for &id in row {
+                let symbol = match id {
+                    None => '.',
+                    Some(id) => *symbols.entry(id).or_insert_with(|| {
+                        let symbol = SYMBOLS[legend.len()] as char;
+                        legend.push(format!("{symbol}={id}"));
+                        symbol
+                    }),
+                };
+                result.push(symbol);
+            }
+            writeln!(
+                result,
+                "{}  {}",
+                " ".repeat(width - row.len()),
+                legend.join(" ")
+            )
+            .unwrap();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_length_and_csv() {
+        let layout = Layout::from_extents([(0, 0, 1), (1, 3, 3), (2, 10, 5)], 15);
+
+        assert_eq!(
+            layout.render(RenderFormat::Runs, 0),
+            "0x1 .x2 1x3 .x4 2x5\n"
+        );
+        assert_eq!(
+            layout.render(RenderFormat::Csv, 0).lines().nth(2),
+            Some("3,1")
+        );
+        assert_eq!(layout.size(), 15);
+    }
+
+    #[test]
+    fn test_wrapped_with_multi_digit_ids() {
+        let layout = Layout::from_extents((0..12).map(|id| (id, id * 2, 1)), 24);
+
+        assert_eq!(
+            layout.render(RenderFormat::Wrapped, 8),
+            "A.B.C.D.  A=0 B=1 C=2 D=3\n\
+             A.B.C.D.  A=4 B=5 C=6 D=7\n\
+             A.B.C.D.  A=8 B=9 C=10 D=11\n"
+        );
+    }
+
+    #[test]
+    fn test_fragmented_files() {
+        let layout = Layout::from_extents([(0, 0, 2), (1, 2, 1), (0, 3, 1)], 6);
+
+        assert_eq!(layout.render(RenderFormat::Runs, 0), "0x2 1x1 0x1 .x2\n");
+    }
+}
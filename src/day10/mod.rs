@@ -1,11 +1,14 @@
 use std::collections::HashSet;
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask},
+    aoc_params::Params,
+};
 
 pub struct Day10;
 
 impl AocTask for Day10 {
-    fn solve_a(&self, contents: String) -> AocResult {
+    fn solve_a_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let mut map = Vec::new();
         for line in contents.lines() {
             map.push(
@@ -73,7 +76,7 @@ impl AocTask for Day10 {
         Ok(sum_of_scores)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
+    fn solve_b_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let mut map = Vec::new();
         for line in contents.lines() {
             map.push(
@@ -1,112 +1,314 @@
 use std::{
-    collections::HashMap,
-    hash::Hash,
-    ops::{Add, Div},
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Display,
 };
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
 
 pub struct Day11;
 
 impl AocTask for Day11 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut stones = contents
-            .split_whitespace()
-            .map(|v| v.parse::<u64>())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
-
-        for _ in 0..25 {
-            blink(&mut stones);
-        }
-        // println!("{:?}", &stones);
-        Ok(stones.len() as i64)
-    }
-
-    fn solve_b(&self, contents: String) -> AocResult {
-        let stones = contents
-            .split_whitespace()
-            .map(|v| v.parse::<u64>())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
-
-        let mut stones = stones.iter().fold(HashMap::new(), |mut map, &stone| {
-            let count = map.get(&stone).unwrap_or(&0u64);
-            map.insert(stone, count + 1);
-            map
-        });
-
-        for i in 0..75 {
-            println!("Iteration: {i}, len: {}", stones.len());
-            stones = blink_b(&stones);
-        }
-        // println!("{:?}", &stones);
-        Ok(stones.values().fold(0u64, |count, c| count + c) as i64)
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let blinks = match part {
+            TaskPart::A => "25",
+            TaskPart::B => "75",
+        };
+        vec![ParamSpec::new("blinks", ParamKind::UInt, blinks)]
     }
-}
 
-fn blink(stones: &mut Vec<u64>) {
-    let mut i = 0;
-    while i < stones.len() {
-        let offset = on_change(stones, i);
-        i += offset + 1;
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_after_blinks(&contents, params.uint("blinks")? as usize)
     }
-}
 
-fn on_change(stones: &mut Vec<u64>, i: usize) -> usize {
-    let stone = stones.get_mut(i).unwrap();
-    if *stone == 0 {
-        *stone = 1;
-        return 0;
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        count_after_blinks(&contents, params.uint("blinks")? as usize)
     }
-    let n_digits = n_digits(*stone);
-    if n_digits % 2 == 0 {
-        let p = 10u64.pow(n_digits / 2);
-        let left = (*stone).div(p);
-        *stone = *stone % p;
-        stones.insert(i, left);
-        return 1;
+}
+
+fn count_after_blinks(contents: &str, blinks: usize) -> AocResult {
+    let stones = parse_stones(contents)?;
+
+    let evolution = StoneCounter::default().evolve(&stones, blinks)?;
+    match evolution.fixed_point {
+        Some(blink) => println!(
+            "{} distinct stone values, unchanged since blink {blink}",
+            evolution.distinct_values
+        ),
+        None => println!("{} distinct stone values", evolution.distinct_values),
     }
 
-    *stone = *stone * 2024;
-    0
+    let count = evolution.count;
+    count
+        .to_u128()
+        .and_then(|c| i64::try_from(c).ok())
+        .ok_or(format!("{count} stones do not fit into the result"))
 }
 
-fn blink_b(stones: &HashMap<u64, u64>) -> HashMap<u64, u64> {
-    let mut next = HashMap::new();
-    for (&stone, &count) in stones {
-        on_change_b(&mut next, stone, count);
+fn parse_stones(contents: &str) -> Result<Vec<u64>, String> {
+    contents
+        .split_whitespace()
+        .map(|v| v.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Counts stones without materializing them. The stones a single stone turns into and the
+/// counts per `(stone, blinks)` of every queried stone are cached.
+#[derive(Default)]
+pub struct StoneCounter {
+    blinks: HashMap<u64, Vec<u64>>,
+    counts: HashMap<(u64, usize), StoneCount>,
+}
+
+/// Result of blinking at a line of stones.
+pub struct Evolution {
+    pub count: StoneCount,
+    /// Number of distinct engraved values after the last blink
+    pub distinct_values: usize,
+    /// First blink after which the set of distinct values did not change anymore
+    pub fixed_point: Option<usize>,
+}
+
+impl StoneCounter {
+    pub fn count_stones(&mut self, stone: u64, blinks: usize) -> Result<StoneCount, String> {
+        if !self.counts.contains_key(&(stone, blinks)) {
+            self.evolve(&[stone], blinks)?;
+        }
+        Ok(self.counts[&(stone, blinks)].clone())
+    }
+
+    /// Blinks `blinks` times, tracking which values are engraved after every blink. The
+    /// stones are then counted one blink level at a time over the distinct values, so
+    /// neither the stones nor the counts of earlier levels are kept around.
+    pub fn evolve(&mut self, stones: &[u64], blinks: usize) -> Result<Evolution, String> {
+        // blink after which every value shows up for the first time
+        let mut first_seen: HashMap<u64, usize> = stones.iter().map(|&s| (s, 0)).collect();
+        let mut values: HashSet<u64> = stones.iter().copied().collect();
+        let mut fixed_point = None;
+        for blink in 1..=blinks {
+            let mut next = HashSet::with_capacity(values.len());
+            for &stone in &values {
+                next.extend(self.blink(stone)?.iter().copied());
+            }
+            for &value in &next {
+                first_seen.entry(value).or_insert(blink);
+            }
+            match (next == values, fixed_point) {
+                (true, None) => fixed_point = Some(blink - 1),
+                (false, _) => fixed_point = None,
+                _ => (),
+            }
+            values = next;
+        }
+
+        // stones every value turns into after `level` blinks, for the values that show up
+        // early enough to be blinked at `level` more times
+        let mut counts: HashMap<u64, StoneCount> = first_seen
+            .keys()
+            .map(|&stone| (stone, StoneCount::ONE))
+            .collect();
+        for level in 1..=blinks {
+            let mut next = HashMap::with_capacity(counts.len());
+            for (&stone, &first) in &first_seen {
+                if first + level > blinks {
+                    continue;
+                }
+                let mut count = StoneCount::ZERO;
+                for child in &self.blinks[&stone] {
+                    count.add(&counts[child]);
+                }
+                next.insert(stone, count);
+            }
+            counts = next;
+        }
+
+        for &stone in stones {
+            self.counts.insert((stone, blinks), counts[&stone].clone());
+        }
+        let mut count = StoneCount::ZERO;
+        for &stone in stones {
+            count.add(&self.count_stones(stone, blinks)?);
+        }
+        Ok(Evolution {
+            count,
+            distinct_values: values.len(),
+            fixed_point,
+        })
+    }
+
+    fn blink(&mut self, stone: u64) -> Result<&[u64], String> {
+        if let Entry::Vacant(entry) = self.blinks.entry(stone) {
+            entry.insert(blink(stone)?);
+        }
+        Ok(&self.blinks[&stone])
     }
-    next
 }
 
-#[inline]
-fn on_change_b(stones: &mut HashMap<u64, u64>, stone: u64, count: u64) {
+fn blink(stone: u64) -> Result<Vec<u64>, String> {
     if stone == 0 {
-        update_add(stones, 1, count);
-        return;
+        return Ok(vec![1]);
     }
     let n_digits = n_digits(stone);
-    if n_digits % 2 == 0 {
+    if n_digits.is_multiple_of(2) {
         let p = 10u64.pow(n_digits / 2);
-        update_add(stones, stone / p, count);
-        update_add(stones, stone % p, count);
-        return;
+        return Ok(vec![stone / p, stone % p]);
     }
-    update_add(stones, stone * 2024, count);
-}
-
-#[inline]
-fn update_add<K, V>(map: &mut HashMap<K, V>, key: K, value: V)
-where
-    K: Eq + Hash,
-    V: Default + Copy + Add<V, Output = V>,
-{
-    let v = map.get(&key).map(|v| *v).unwrap_or(Default::default());
-    map.insert(key, v + value);
+    Ok(vec![stone.checked_mul(2024).ok_or(format!(
+        "stone {stone} overflows when multiplied by 2024"
+    ))?])
 }
 
 #[inline]
 fn n_digits(n: u64) -> u32 {
     n.checked_ilog10().unwrap_or(0) + 1
 }
+
+/// Unsigned count that starts out as a `u128` and continues as little endian `u64` limbs
+/// once that overflows, which happens after a couple of hundred blinks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoneCount {
+    Small(u128),
+    Big(Vec<u64>),
+}
+
+impl StoneCount {
+    const ZERO: StoneCount = StoneCount::Small(0);
+    const ONE: StoneCount = StoneCount::Small(1);
+
+    pub fn to_u128(&self) -> Option<u128> {
+        match self {
+            Self::Small(v) => Some(*v),
+            Self::Big(_) => None,
+        }
+    }
+
+    fn add(&mut self, other: &StoneCount) {
+        if let (Self::Small(a), Self::Small(b)) = (&*self, other) {
+            if let Some(sum) = a.checked_add(*b) {
+                *self = Self::Small(sum);
+                return;
+            }
+        }
+        let mut limbs = self.limbs();
+        let other = other.limbs();
+        limbs.resize(limbs.len().max(other.len()), 0);
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, c1) = limb.overflowing_add(other.get(i).copied().unwrap_or(0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        *self = Self::Big(limbs);
+    }
+
+    fn limbs(&self) -> Vec<u64> {
+        match self {
+            Self::Small(v) => vec![*v as u64, (*v >> 64) as u64],
+            Self::Big(limbs) => limbs.clone(),
+        }
+    }
+}
+
+impl Display for StoneCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self::Big(limbs) = self else {
+            return write!(f, "{}", self.to_u128().unwrap());
+        };
+        // repeatedly divide by 10^19, the largest power of ten fitting into a limb
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut limbs = limbs.clone();
+        let mut chunks = Vec::new();
+        while limbs.iter().any(|&l| l != 0) {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let current = (remainder << 64) | *limb as u128;
+                *limb = (current / CHUNK as u128) as u64;
+                remainder = current % CHUNK as u128;
+            }
+            chunks.push(remainder as u64);
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((most_significant, rest)) => {
+                write!(f, "{most_significant}")?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{chunk:019}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let mut counter = StoneCounter::default();
+
+        assert_eq!(counter.evolve(&[125, 17], 6)?.count, StoneCount::Small(22));
+        assert_eq!(
+            counter.evolve(&[125, 17], 25)?.count,
+            StoneCount::Small(55312)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_stones_is_cached() -> Result<(), String> {
+        let mut counter = StoneCounter::default();
+
+        let first = counter.count_stones(125, 40)?;
+        assert!(counter.counts.contains_key(&(125, 40)));
+        assert_eq!(counter.count_stones(125, 40)?, first);
+        assert_eq!(counter.evolve(&[125], 40)?.count, first);
+        Ok(())
+    }
+
+    #[test]
+    fn test_thousands_of_blinks() -> Result<(), String> {
+        let mut counter = StoneCounter::default();
+
+        let evolution = counter.evolve(&[0], 1000)?;
+
+        assert!(matches!(evolution.count, StoneCount::Big(_)));
+        let fixed_point = evolution.fixed_point.ok_or("no fixed point")?;
+        assert_eq!(
+            counter.evolve(&[0], fixed_point)?.distinct_values,
+            evolution.distinct_values
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blinks_do_not_grow_the_stack() -> Result<(), String> {
+        let mut counter = StoneCounter::default();
+
+        let evolution = counter.evolve(&[125, 17], 5000)?;
+
+        assert_eq!(evolution.distinct_values, 54);
+        assert_eq!(counter.count_stones(17, 5000)?.to_u128(), None);
+        assert_eq!(
+            counter.evolve(&[125, 17], 75)?.count,
+            StoneCount::Small(65601038650482)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_big_count() {
+        let mut count = StoneCount::Small(u128::MAX);
+        count.add(&StoneCount::Small(1));
+
+        assert_eq!(count.to_string(), "340282366920938463463374607431768211456");
+    }
+}
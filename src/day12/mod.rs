@@ -1,13 +1,16 @@
 use std::collections::{BTreeSet, HashSet};
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask},
+    aoc_params::Params,
+};
 
 pub struct Day12;
 
 type Position = (usize, usize);
 
 impl AocTask for Day12 {
-    fn solve_a(&self, contents: String) -> AocResult {
+    fn solve_a_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let map: Vec<Vec<_>> = contents.lines().map(|l| l.chars().collect()).collect();
 
         let mut regions = Vec::new();
@@ -46,7 +49,7 @@ impl AocTask for Day12 {
         Ok(result)
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
+    fn solve_b_with_params(&self, contents: String, _params: &Params) -> AocResult {
         let map: Vec<Vec<_>> = contents.lines().map(|l| l.chars().collect()).collect();
 
         let mut regions = Vec::new();
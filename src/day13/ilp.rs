@@ -0,0 +1,356 @@
+use std::cmp::Ordering;
+
+use crate::aoc_util::gcd;
+
+/// Upper limit of branch and bound nodes before the search is given up.
+const MAX_NODES: usize = 100_000;
+/// Every vertex of the relaxation is enumerated, so this keeps the basis enumeration bounded.
+pub const MAX_VARIABLES: usize = 12;
+
+/// Integer solution with the minimal cost.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub values: Vec<u64>,
+    pub cost: u64,
+}
+
+/// Minimizes `sum(costs[i] * x[i])` subject to `sum(x[i] * columns[i]) == target` with
+/// non-negative integers `x`. Solves the LP relaxation exactly on rationals and branches
+/// on fractional values.
+pub fn minimize(
+    columns: &[(i128, i128)],
+    costs: &[i128],
+    target: (i128, i128),
+) -> Result<Option<Solution>, String> {
+    if columns.len() != costs.len() {
+        return Err(format!(
+            "{} columns but {} costs",
+            columns.len(),
+            costs.len()
+        ));
+    }
+    if columns.len() > MAX_VARIABLES {
+        return Err(format!("at most {MAX_VARIABLES} variables are supported"));
+    }
+    if costs.iter().any(|&c| c < 0) {
+        return Err("costs must not be negative".to_owned());
+    }
+    let problem = Problem {
+        columns,
+        costs,
+        target,
+    };
+
+    let mut best: Option<(i128, Vec<i128>)> = None;
+    let mut stack = vec![Bounds {
+        lower: vec![0; columns.len()],
+        upper: vec![None; columns.len()],
+    }];
+    let mut nodes = 0;
+    while let Some(bounds) = stack.pop() {
+        nodes += 1;
+        if nodes > MAX_NODES {
+            return Err(format!("no optimum found within {MAX_NODES} nodes"));
+        }
+        let Some(vertex) = problem.solve_relaxation(&bounds) else {
+            continue;
+        };
+        if let Some((best_cost, _)) = best {
+            if vertex.cost.ceil() >= best_cost {
+                continue;
+            }
+        }
+        let fractional = vertex
+            .values
+            .iter()
+            .enumerate()
+            .find(|(_, v)| !v.is_integer());
+        match fractional {
+            None => {
+                best = Some((
+                    vertex.cost.floor(),
+                    vertex.values.iter().map(Ratio::floor).collect(),
+                ));
+            }
+            Some((i, value)) => {
+                let mut up = bounds.clone();
+                up.lower[i] = value.ceil();
+                stack.push(up);
+                let mut down = bounds;
+                down.upper[i] = Some(value.floor());
+                stack.push(down);
+            }
+        }
+    }
+
+    best.map(|(cost, values)| {
+        Ok(Solution {
+            values: values
+                .into_iter()
+                .map(|v| u64::try_from(v).map_err(|e| e.to_string()))
+                .collect::<Result<_, _>>()?,
+            cost: u64::try_from(cost).map_err(|e| e.to_string())?,
+        })
+    })
+    .transpose()
+}
+
+struct Problem<'a> {
+    columns: &'a [(i128, i128)],
+    costs: &'a [i128],
+    target: (i128, i128),
+}
+
+#[derive(Clone)]
+struct Bounds {
+    lower: Vec<i128>,
+    upper: Vec<Option<i128>>,
+}
+
+struct Vertex {
+    values: Vec<Ratio>,
+    cost: Ratio,
+}
+
+impl Problem<'_> {
+    /// With two constraints every vertex has at most two basic variables, all others sit on
+    /// one of their bounds. Enumerating all of them yields the optimum of the relaxation.
+    fn solve_relaxation(&self, bounds: &Bounds) -> Option<Vertex> {
+        let n = self.columns.len();
+        let mut bases: Vec<Vec<usize>> = vec![vec![]];
+        for i in 0..n {
+            bases.push(vec![i]);
+            for j in (i + 1)..n {
+                bases.push(vec![i, j]);
+            }
+        }
+
+        let mut best: Option<Vertex> = None;
+        for basis in bases {
+            let non_basic = (0..n).filter(|i| !basis.contains(i)).collect::<Vec<_>>();
+            let mut assignment = non_basic
+                .iter()
+                .map(|&i| bounds.lower[i])
+                .collect::<Vec<_>>();
+            loop {
+                if let Some(vertex) = self.vertex(bounds, &basis, &non_basic, &assignment) {
+                    if best.as_ref().is_none_or(|b| vertex.cost < b.cost) {
+                        best = Some(vertex);
+                    }
+                }
+                // advance to the next combination of lower/upper bounds
+                let mut k = 0;
+                while k < non_basic.len() {
+                    let i = non_basic[k];
+                    match bounds.upper[i] {
+                        Some(upper) if assignment[k] != upper => {
+                            assignment[k] = upper;
+                            break;
+                        }
+                        _ => {
+                            assignment[k] = bounds.lower[i];
+                            k += 1;
+                        }
+                    }
+                }
+                if k == non_basic.len() {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    fn vertex(
+        &self,
+        bounds: &Bounds,
+        basis: &[usize],
+        non_basic: &[usize],
+        assignment: &[i128],
+    ) -> Option<Vertex> {
+        let n = self.columns.len();
+        let (mut r0, mut r1) = self.target;
+        let mut values = vec![Ratio::from(0); n];
+        for (&i, &value) in non_basic.iter().zip(assignment) {
+            let (c0, c1) = self.columns[i];
+            r0 -= c0 * value;
+            r1 -= c1 * value;
+            values[i] = Ratio::from(value);
+        }
+
+        match *basis {
+            [] => {
+                if r0 != 0 || r1 != 0 {
+                    return None;
+                }
+            }
+            [i] => {
+                let (c0, c1) = self.columns[i];
+                values[i] = if c0 != 0 {
+                    if r0 * c1 != r1 * c0 {
+                        return None;
+                    }
+                    Ratio::new(r0, c0)
+                } else if c1 != 0 {
+                    if r0 != 0 {
+                        return None;
+                    }
+                    Ratio::new(r1, c1)
+                } else {
+                    return None;
+                };
+            }
+            [i, j] => {
+                let (i0, i1) = self.columns[i];
+                let (j0, j1) = self.columns[j];
+                let det = i0 * j1 - i1 * j0;
+                if det == 0 {
+                    return None;
+                }
+                values[i] = Ratio::new(r0 * j1 - r1 * j0, det);
+                values[j] = Ratio::new(i0 * r1 - i1 * r0, det);
+            }
+            _ => unreachable!(),
+        }
+
+        for &i in basis {
+            if values[i] < Ratio::from(bounds.lower[i]) {
+                return None;
+            }
+            if let Some(upper) = bounds.upper[i] {
+                if values[i] > Ratio::from(upper) {
+                    return None;
+                }
+            }
+        }
+
+        let cost = values
+            .iter()
+            .zip(self.costs)
+            .fold(Ratio::from(0), |cost, (value, &c)| {
+                cost.add(&value.scale(c))
+            });
+        Some(Vertex { values, cost })
+    }
+}
+
+/// Exact fraction, the denominator is always positive.
+#[derive(Clone, Copy, Debug)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Ratio {
+        let sign = den.signum();
+        let divisor = gcd(num.abs(), den.abs()).max(1);
+        Ratio {
+            num: sign * num / divisor,
+            den: sign * den / divisor,
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    fn ceil(&self) -> i128 {
+        -(-self.num).div_euclid(self.den)
+    }
+
+    fn add(&self, other: &Ratio) -> Ratio {
+        Ratio::new(
+            self.num * other.den + other.num * self.den,
+            self.den * other.den,
+        )
+    }
+
+    fn scale(&self, factor: i128) -> Ratio {
+        Ratio::new(self.num * factor, self.den)
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(value: i128) -> Self {
+        Ratio { num: value, den: 1 }
+    }
+}
+
+impl PartialEq for Ratio {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Ratio {}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_buttons() -> Result<(), String> {
+        let solution = minimize(&[(34, 94), (67, 22)], &[3, 1], (5400, 8400))?;
+
+        assert_eq!(
+            solution,
+            Some(Solution {
+                values: vec![80, 40],
+                cost: 280
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_solution() -> Result<(), String> {
+        let solution = minimize(&[(66, 26), (21, 67)], &[3, 1], (12176, 12748))?;
+
+        assert_eq!(solution, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_three_buttons_prefers_cheapest_combination() -> Result<(), String> {
+        // (4, 4) is reachable with 4 presses of A, 2 of B or 2 of A plus 1 of B
+        let solution = minimize(&[(1, 1), (2, 2), (1, 0)], &[1, 3, 1], (4, 4))?;
+
+        assert_eq!(
+            solution,
+            Some(Solution {
+                values: vec![4, 0, 0],
+                cost: 4
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_three_buttons_needs_branching() -> Result<(), String> {
+        let solution = minimize(&[(3, 0), (0, 3), (2, 2)], &[1, 1, 5], (7, 7))?;
+
+        assert_eq!(
+            solution,
+            Some(Solution {
+                values: vec![1, 1, 2],
+                cost: 12
+            })
+        );
+        Ok(())
+    }
+}
@@ -1,139 +1,175 @@
 use std::str::FromStr;
 
-use ndarray::prelude::*;
-use ndarray_linalg::Solve;
+use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc_core::{AocResult, AocTask};
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
 
-pub struct Day13;
-
-const BUTTON_A_COST: usize = 3;
-const BUTTON_B_COST: usize = 1;
+mod ilp;
 
-const PRIZE_OFFSET: usize = 10000000000000;
+pub struct Day13;
 
 impl AocTask for Day13 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let claw_machines: Vec<ClawMachine> = contents
-            .split("\n\n")
-            .map(|s| s.parse())
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let mut result = 0usize;
-        for claw_machine in claw_machines {
-            let btn: Array2<f64> = array![
-                [
-                    claw_machine.button_a.0 as f64,
-                    claw_machine.button_b.0 as f64
-                ],
-                [
-                    claw_machine.button_a.1 as f64,
-                    claw_machine.button_b.1 as f64
-                ]
-            ];
-            let prize: Array1<f64> =
-                array![claw_machine.prize.0 as f64, claw_machine.prize.1 as f64];
-
-            let solution = btn.solve_into(prize).map_err(|e| e.to_string())?;
-
-            let a = solution[0].round() as usize;
-            let b = solution[1].round() as usize;
-
-            if a * claw_machine.button_a.0 + b * claw_machine.button_b.0 != claw_machine.prize.0
-                || a * claw_machine.button_a.1 + b * claw_machine.button_b.1 != claw_machine.prize.1
-            {
-                continue;
-            }
-
-            result += a * BUTTON_A_COST + b * BUTTON_B_COST;
-
-            // println!("{solution:?}");
-        }
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let prize_offset = match part {
+            TaskPart::A => "0",
+            TaskPart::B => "10000000000000",
+        };
+        vec![
+            // button A costs 3 tokens, button B costs 1
+            ParamSpec::new("costs", ParamKind::List, "3,1"),
+            ParamSpec::new("prize_offset", ParamKind::UInt, prize_offset),
+        ]
+    }
+
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        total_cost(&contents, params)
+    }
 
-        Ok(result as i64)
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        total_cost(&contents, params)
     }
+}
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        let claw_machines: Vec<ClawMachine> = contents
-            .split("\n\n")
-            .map(|s| s.parse())
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let mut result = 0usize;
-        for claw_machine in claw_machines {
-            let btn: Array2<f64> = array![
-                [
-                    claw_machine.button_a.0 as f64,
-                    claw_machine.button_b.0 as f64
-                ],
-                [
-                    claw_machine.button_a.1 as f64,
-                    claw_machine.button_b.1 as f64
-                ]
-            ];
-            let prize: Array1<f64> = array![
-                claw_machine.prize.0 as f64 + PRIZE_OFFSET as f64,
-                claw_machine.prize.1 as f64 + PRIZE_OFFSET as f64
-            ];
-
-            let solution = btn.solve_into(prize).map_err(|e| e.to_string())?;
-            println!("{solution:?}");
-
-            let a = solution[0].round() as usize;
-            let b = solution[1].round() as usize;
-            // println!("a={a} b={b}");
-            // println!(
-            //     "sol={}, prize={}",
-            //     a * claw_machine.button_a.0 + b * claw_machine.button_b.0,
-            //     claw_machine.prize.0
-            // );
-
-            if a * claw_machine.button_a.0 + b * claw_machine.button_b.0 - PRIZE_OFFSET
-                != claw_machine.prize.0
-                || a * claw_machine.button_a.1 + b * claw_machine.button_b.1 - PRIZE_OFFSET
-                    != claw_machine.prize.1
-            {
-                continue;
-            }
-
-            result += a * BUTTON_A_COST + b * BUTTON_B_COST;
+fn total_cost(contents: &str, params: &Params) -> AocResult {
+    let cost_model = CostModel::new(params.list("costs")?.iter().map(|&c| c as usize).collect());
+    let prize_offset = params.uint("prize_offset")? as usize;
+
+    let claw_machines: Vec<ClawMachine> = contents
+        .split("\n\n")
+        .map(|s| s.parse())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut result = 0u64;
+    for claw_machine in claw_machines {
+        if let Some(solution) = claw_machine.solve(&cost_model, prize_offset)? {
+            result += solution.cost;
         }
+    }
 
-        Ok(result as i64)
+    result.try_into().map_err(|e| format!("{e}"))
+}
+
+/// Token costs per button, in the order the buttons are listed for a claw machine.
+pub struct CostModel {
+    button_costs: Vec<usize>,
+}
+impl CostModel {
+    pub fn new(button_costs: Vec<usize>) -> Self {
+        CostModel { button_costs }
     }
 }
 
 type Button = (usize, usize);
 type Position = (usize, usize);
 
-struct ClawMachine {
-    button_a: Button,
-    button_b: Button,
+pub struct ClawMachine {
+    buttons: Vec<Button>,
     prize: Position,
 }
+
+pub type Solution = ilp::Solution;
+
+impl ClawMachine {
+    /// Finds the cheapest number of presses per button that moves the claw onto the prize,
+    /// whose coordinates are both shifted by `prize_offset`. `None` if the prize cannot be won.
+    pub fn solve(
+        &self,
+        cost_model: &CostModel,
+        prize_offset: usize,
+    ) -> Result<Option<Solution>, String> {
+        if cost_model.button_costs.len() < self.buttons.len() {
+            return Err(format!(
+                "cost model covers {} buttons, but claw machine has {}",
+                cost_model.button_costs.len(),
+                self.buttons.len()
+            ));
+        }
+        let columns = self
+            .buttons
+            .iter()
+            .map(|&(x, y)| (x as i128, y as i128))
+            .collect::<Vec<_>>();
+        let costs = cost_model.button_costs[..self.buttons.len()]
+            .iter()
+            .map(|&c| c as i128)
+            .collect::<Vec<_>>();
+        let (x, y) = self.prize;
+        let target = (
+            x as i128 + prize_offset as i128,
+            y as i128 + prize_offset as i128,
+        );
+
+        ilp::minimize(&columns, &costs, target)
+    }
+}
+
+lazy_static! {
+    static ref BUTTON_PATTERN: Regex = Regex::new(r"^Button [A-Z]: X\+(\d+), Y\+(\d+)$").unwrap();
+    static ref PRIZE_PATTERN: Regex = Regex::new(r"^Prize: X=(\d+), Y=(\d+)$").unwrap();
+}
+
 impl FromStr for ClawMachine {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let raw = s.trim().lines().collect::<Vec<_>>();
 
-        if raw.len() != 3 {
-            return Err("expected 3 lines".to_owned());
+        let (raw_prize, raw_buttons) = raw
+            .split_last()
+            .ok_or("expected at least one button and a prize".to_owned())?;
+        if raw_buttons.is_empty() {
+            return Err("expected at least one button and a prize".to_owned());
+        }
+        if raw_buttons.len() > ilp::MAX_VARIABLES {
+            return Err(format!(
+                "at most {} buttons are supported",
+                ilp::MAX_VARIABLES
+            ));
         }
         Ok(ClawMachine {
-            button_a: parse_line(raw[0], r"^Button A: X\+(\d+), Y\+(\d+)$")?,
-            button_b: parse_line(raw[1], r"^Button B: X\+(\d+), Y\+(\d+)$")?,
-            prize: parse_line(raw[2], r"^Prize: X=(\d+), Y=(\d+)$")?,
+            buttons: raw_buttons
+                .iter()
+                .map(|line| parse_line(line, &BUTTON_PATTERN))
+                .collect::<Result<Vec<_>, _>>()?,
+            prize: parse_line(raw_prize, &PRIZE_PATTERN)?,
         })
     }
 }
 
-fn parse_line(s: &str, pattern: &str) -> Result<(usize, usize), String> {
-    let btn_a = Regex::new(pattern)
-        .unwrap()
+fn parse_line(s: &str, pattern: &Regex) -> Result<(usize, usize), String> {
+    let captures = pattern
         .captures(s.trim())
         .ok_or(format!("invalid input: {s}"))?;
-    let [btn_a_x, btn_a_y] = btn_a.extract().1;
-    Ok((btn_a_x.parse().unwrap(), btn_a_y.parse().unwrap()))
+    let [x, y] = captures.extract().1;
+    Ok((
+        x.parse().map_err(|e| format!("invalid number {x}: {e}"))?,
+        y.parse().map_err(|e| format!("invalid number {y}: {e}"))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::aoc_core::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_prize_offset_near_usize_max() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        let overrides = [("prize_offset".to_owned(), "18446744073709551530".to_owned())];
+        let params = Params::resolve(&Day13.params(&TaskPart::B), None, &overrides)?;
+
+        assert_eq!(Day13.solve_a(contents.clone())?, 480);
+        assert_eq!(
+            Day13.solve_b_with_params(contents, &params)?,
+            648128845833038577
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,250 @@
+use std::{fs, ops::Range, path::Path, str::FromStr};
+
+use super::Robot;
+
+const BACKGROUND: [u8; 3] = [0, 0, 0];
+const ROBOT: [u8; 3] = [80, 220, 80];
+const SEPARATOR: [u8; 3] = [90, 90, 90];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+        }
+    }
+}
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ppm" => Ok(Self::Ppm),
+            "png" => Ok(Self::Png),
+            _ => Err(format!("invalid image format: {s}")),
+        }
+    }
+}
+
+/// RGB image, row by row.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![color; width * height],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.encode_ppm(),
+            ImageFormat::Png => self.encode_png(),
+        }
+    }
+
+    pub fn write(&self, path: &Path, format: ImageFormat) -> Result<(), String> {
+        fs::write(path, self.encode(format)).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    fn encode_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    fn encode_png(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bit depth, truecolor, default compression, filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut scanlines = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            scanlines.push(0);
+            scanlines.extend(row.iter().flatten());
+        }
+
+        let mut bytes = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        write_png_chunk(&mut bytes, b"IHDR", &header);
+        write_png_chunk(&mut bytes, b"IDAT", &zlib_stored(&scanlines));
+        write_png_chunk(&mut bytes, b"IEND", &[]);
+        bytes
+    }
+}
+
+/// One pixel per tile, each tile scaled up to `scale` x `scale` pixels.
+pub fn render_frame(
+    robots: &[Robot],
+    seconds: usize,
+    width: usize,
+    height: usize,
+    scale: usize,
+) -> Image {
+    let mut image = Image::new(width * scale, height * scale, BACKGROUND);
+    draw_robots(&mut image, (0, 0), robots, seconds, width, height, scale);
+    image
+}
+
+/// Tiles the frames of all `seconds` into one image, `columns` frames per row, separated by
+/// a one pixel line.
+pub fn render_contact_sheet(
+    robots: &[Robot],
+    seconds: Range<usize>,
+    columns: usize,
+    width: usize,
+    height: usize,
+    scale: usize,
+) -> Image {
+    let columns = columns.max(1).min(seconds.len().max(1));
+    let rows = seconds.len().div_ceil(columns);
+    let (tile_width, tile_height) = (width * scale + 1, height * scale + 1);
+    let mut image = Image::new(
+        columns * tile_width - 1,
+        (rows * tile_height).max(1) - 1,
+        SEPARATOR,
+    );
+    for (i, t) in seconds.enumerate() {
+        let origin = ((i % columns) * tile_width, (i / columns) * tile_height);
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                image.set(origin.0 + x, origin.1 + y, BACKGROUND);
+            }
+        }
+        draw_robots(&mut image, origin, robots, t, width, height, scale);
+    }
+    image
+}
+
+fn draw_robots(
+    image: &mut Image,
+    (ox, oy): (usize, usize),
+    robots: &[Robot],
+    seconds: usize,
+    width: usize,
+    height: usize,
+    scale: usize,
+) {
+    for robot in robots {
+        let (x, y) = robot.position_at(seconds, width, height);
+        for dy in 0..scale {
+            for dx in 0..scale {
+                image.set(ox + x * scale + dx, oy + y * scale + dy, ROBOT);
+            }
+        }
+    }
+}
+
+fn write_png_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    let start = bytes.len();
+    bytes.extend(kind);
+    bytes.extend(data);
+    let crc = crc32(&bytes[start..]);
+    bytes.extend(crc.to_be_bytes());
+}
+
+/// zlib stream of uncompressed deflate blocks, which every PNG decoder has to accept.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut bytes = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        bytes.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        bytes.push(is_final as u8);
+        let len = block.len() as u16;
+        bytes.extend(len.to_le_bytes());
+        bytes.extend((!len).to_le_bytes());
+        bytes.extend(block);
+    }
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_encode_ppm() {
+        let mut image = Image::new(2, 1, BACKGROUND);
+        image.set(1, 0, ROBOT);
+
+        assert_eq!(
+            image.encode(ImageFormat::Ppm),
+            [b"P6\n2 1\n255\n".as_slice(), &[0, 0, 0, 80, 220, 80]].concat()
+        );
+    }
+
+    #[test]
+    fn test_encode_png_layout() {
+        let image = Image::new(3, 2, ROBOT);
+
+        let bytes = image.encode(ImageFormat::Png);
+
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&bytes[12..16], b"IHDR");
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&bytes[bytes.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+
+    #[test]
+    fn test_contact_sheet_dimensions() -> Result<(), String> {
+        let robots = vec!["p=0,0 v=1,1".parse::<Robot>()?];
+
+        let sheet = render_contact_sheet(&robots, 0..5, 3, 4, 3, 2);
+
+        assert_eq!((sheet.width, sheet.height), (3 * 9 - 1, 2 * 7 - 1));
+        assert_eq!(sheet.pixels[0], ROBOT);
+        // second frame starts after the separator, robot moved one tile
+        assert_eq!(sheet.pixels[9 + 2 + 2 * sheet.width], ROBOT);
+        Ok(())
+    }
+}
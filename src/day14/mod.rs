@@ -1,129 +1,249 @@
-use std::{collections::HashSet, num::ParseIntError, path::Path, str::FromStr};
+use std::{fs, num::ParseIntError, path::Path, str::FromStr};
 
-use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc_core::{read_file, AocResult, AocTask};
+use frames::ImageFormat;
+
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+    aoc_util::gcd,
+};
+
+mod frames;
 
 pub struct Day14;
 
 impl AocTask for Day14 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut lines = contents.lines();
-        let raw_dim = lines.next().ok_or("Empty input".to_owned())?;
-        let (width, height) = raw_dim
-            .split(",")
-            .map(|d| parse_number(d))
-            .collect::<Result<Vec<_>, _>>()?
-            .iter()
-            .map(|&d| d)
-            .collect_tuple::<(usize, usize)>()
-            .ok_or(format!("Invalid dimensions: {raw_dim}"))?;
-
-        let mut robots = lines.map(Robot::from_str).collect::<Result<Vec<_>, _>>()?;
-
-        for _ in 0..100 {
-            for robot in &mut robots {
-                let (x, y) = robot.position;
-                let (vx, vy) = robot.velocity;
-                robot.position = (wrapping_add(x, vx, width), wrapping_add(y, vy, height));
-            }
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let mut params = vec![ParamSpec::new("grid", ParamKind::Dimensions, "101x103")
+            .with_input_default("sample.txt", "11x7")];
+        if *part == TaskPart::A {
+            params.push(ParamSpec::new("seconds", ParamKind::UInt, "100"));
         }
+        params.extend([
+            ParamSpec::new("frames", ParamKind::Range, "0..0"),
+            ParamSpec::new("contact_sheet", ParamKind::Range, "0..0"),
+            ParamSpec::new("sheet_columns", ParamKind::UInt, "10"),
+            ParamSpec::new("frame_scale", ParamKind::UInt, "1"),
+            ParamSpec::new("frame_format", ParamKind::Text, "png"),
+            ParamSpec::new("frames_dir", ParamKind::Text, "frames"),
+        ]);
+        params
+    }
 
-        let mut quadrants = [0usize, 0, 0, 0];
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let (width, height) = grid(params)?;
 
-        for robot in robots {
-            let (x, y) = robot.position;
+        let robots = parse_robots(&contents)?;
+        export_frames(&robots, width, height, params)?;
 
-            let mut quadrant = 0usize;
-            if width % 2 == 1 && x == width / 2 {
-                continue;
-            }
-            if height % 2 == 1 && y == height / 2 {
-                continue;
-            }
-            if x >= width / 2 {
-                quadrant |= 1;
-            }
-            if y >= height / 2 {
-                quadrant |= 1 << 1;
-            }
-            quadrants[quadrant] += 1;
-        }
-        println!("{:?}", quadrants);
-        Ok(quadrants.iter().product::<usize>() as i64)
-    }
-
-    fn solve_b(&self, contents: String) -> AocResult {
-        let mut lines = contents.lines();
-        let raw_dim = lines.next().ok_or("Empty input".to_owned())?;
-        let (width, height) = raw_dim
-            .split(",")
-            .map(|d| parse_number(d))
-            .collect::<Result<Vec<_>, _>>()?
-            .iter()
-            .map(|&d| d)
-            .collect_tuple::<(usize, usize)>()
-            .ok_or(format!("Invalid dimensions: {raw_dim}"))?;
-
-        let mut robots = lines.map(Robot::from_str).collect::<Result<Vec<_>, _>>()?;
-
-        let mut seconds = 0usize;
-        loop {
-            if is_christmas_tree_present(&robots) {
-                break;
-            }
-            for robot in &mut robots {
-                let (x, y) = robot.position;
-                let (vx, vy) = robot.velocity;
-                robot.position = (wrapping_add(x, vx, width), wrapping_add(y, vy, height));
-            }
-            seconds += 1;
-        }
+        let seconds = params.uint("seconds")? as usize;
+        Ok(safety_factor(&robots, seconds, width, height) as i64)
+    }
+
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        let (width, height) = grid(params)?;
+
+        let robots = parse_robots(&contents)?;
+        export_frames(&robots, width, height, params)?;
 
-        Ok(seconds as i64)
+        let detection =
+            detect_christmas_tree(&robots, width, height).ok_or("No robots to look at")?;
+        println!("Best score {:.3}", detection.score);
+
+        Ok(detection.seconds as i64)
     }
 }
 
-fn is_christmas_tree_present(robots: &[Robot]) -> bool {
-    let mut positions = HashSet::new();
-    for robot in robots {
-        positions.insert(robot.position);
+/// Width and height of the room, which needs at least one tile in each direction.
+fn grid(params: &Params) -> Result<(usize, usize), String> {
+    let (width, height) = params.dimensions("grid")?;
+    if width == 0 || height == 0 {
+        return Err(format!("grid {width}x{height} has no tiles"));
     }
-    'robot: for robot in robots {
-        let (x, y) = robot.position;
-        for &(cx, cy) in CHRISTMAS_TREE.iter() {
-            if !positions.contains(&(x + cx, y + cy)) {
-                continue 'robot;
-            }
-        }
-        return true;
+    Ok((width, height))
+}
+
+/// Writes an image per second in `frames` and a single contact sheet of all seconds in
+/// `contact_sheet` into `frames_dir`.
+fn export_frames(
+    robots: &[Robot],
+    width: usize,
+    height: usize,
+    params: &Params,
+) -> Result<(), String> {
+    let seconds = params.range("frames")?;
+    let sheet = params.range("contact_sheet")?;
+    if seconds.is_empty() && sheet.is_empty() {
+        return Ok(());
+    }
+    let format: ImageFormat = params.text("frame_format")?.parse()?;
+    let scale = params.uint("frame_scale")? as usize;
+    if scale == 0 {
+        return Err("frame_scale has to be at least 1".to_owned());
+    }
+    let dir = Path::new(params.text("frames_dir")?);
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+
+    for t in seconds.start as usize..seconds.end as usize {
+        let path = dir.join(format!("frame_{t:05}.{}", format.extension()));
+        frames::render_frame(robots, t, width, height, scale).write(&path, format)?;
     }
-    false
+    if !sheet.is_empty() {
+        let path = dir.join(format!(
+            "contact_sheet_{:05}_{:05}.{}",
+            sheet.start,
+            sheet.end,
+            format.extension()
+        ));
+        let columns = params.uint("sheet_columns")? as usize;
+        frames::render_contact_sheet(
+            robots,
+            sheet.start as usize..sheet.end as usize,
+            columns,
+            width,
+            height,
+            scale,
+        )
+        .write(&path, format)?;
+    }
+    println!("Wrote frames to {}", dir.display());
+    Ok(())
 }
 
-fn wrapping_add(lhs: usize, rhs: i32, bound: usize) -> usize {
-    if rhs < 0 {
-        let arhs = -rhs as usize;
-        if lhs < arhs {
-            return bound - (arhs - lhs);
+/// Product of the number of robots per quadrant after `seconds`, robots on the middle
+/// row or column are not in any quadrant.
+pub fn safety_factor(robots: &[Robot], seconds: usize, width: usize, height: usize) -> usize {
+    let mut quadrants = [0usize, 0, 0, 0];
+
+    for robot in robots {
+        let (x, y) = robot.position_at(seconds, width, height);
+
+        let mut quadrant = 0usize;
+        if width % 2 == 1 && x == width / 2 {
+            continue;
+        }
+        if height % 2 == 1 && y == height / 2 {
+            continue;
         }
-        return lhs - arhs;
+        if x >= width / 2 {
+            quadrant |= 1;
+        }
+        if y >= height / 2 {
+            quadrant |= 1 << 1;
+        }
+        quadrants[quadrant] += 1;
+    }
+    quadrants.iter().product()
+}
+
+/// Number of seconds after which all robots are back at their starting positions.
+pub fn period(width: usize, height: usize) -> usize {
+    width / gcd(width, height) * height
+}
+
+/// Time step at which the robots cluster the most.
+#[derive(Debug, PartialEq)]
+pub struct TreeDetection {
+    pub seconds: usize,
+    /// Variance at `seconds` relative to the average variance, lower is more clustered
+    pub score: f64,
+}
+
+/// The picture shows up when robots are tightly clustered along both axes. The x positions
+/// repeat every `width` seconds and the y positions every `height` seconds, so the time
+/// steps of minimal x and y variance are searched within their own periods and combined
+/// with the chinese remainder theorem.
+pub fn detect_christmas_tree(
+    robots: &[Robot],
+    width: usize,
+    height: usize,
+) -> Option<TreeDetection> {
+    if robots.is_empty() {
+        return None;
     }
-    let arhs = rhs as usize;
-    if bound - lhs <= arhs {
-        return arhs - (bound - lhs);
+    let x_variances = (0..width)
+        .map(|t| variance(robots.iter().map(|r| r.position_at(t, width, height).0)))
+        .collect::<Vec<_>>();
+    let y_variances = (0..height)
+        .map(|t| variance(robots.iter().map(|r| r.position_at(t, width, height).1)))
+        .collect::<Vec<_>>();
+    let x_scores = relative(&x_variances);
+    let y_scores = relative(&y_variances);
+    let score = |t: usize| (x_scores[t % width] + y_scores[t % height]) / 2.0;
+
+    let tx = argmin(&x_scores);
+    let ty = argmin(&y_scores);
+    let seconds = match chinese_remainder(tx, width, ty, height) {
+        Some(seconds) => seconds,
+        // both minima can not happen at the same time, take the best compromise instead
+        None => (0..period(width, height))
+            .min_by(|&a, &b| score(a).total_cmp(&score(b)))
+            .unwrap(),
+    };
+    Some(TreeDetection {
+        seconds,
+        score: score(seconds),
+    })
+}
+
+fn variance<I: Iterator<Item = usize>>(values: I) -> f64 {
+    let values = values.map(|v| v as f64).collect::<Vec<_>>();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n
+}
+
+fn relative(values: &[f64]) -> Vec<f64> {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    if mean == 0.0 {
+        return vec![1.0; values.len()];
     }
+    values.iter().map(|v| v / mean).collect()
+}
 
-    lhs + arhs
+fn argmin(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap()
 }
 
-struct Robot {
+/// Smallest `t` with `t % m == a` and `t % n == b`.
+fn chinese_remainder(a: usize, m: usize, b: usize, n: usize) -> Option<usize> {
+    (0..n / gcd(m, n)).map(|k| a + k * m).find(|t| t % n == b)
+}
+
+fn parse_robots(contents: &str) -> Result<Vec<Robot>, String> {
+    contents.lines().map(Robot::from_str).collect()
+}
+
+pub struct Robot {
     position: (usize, usize),
     velocity: (i32, i32),
 }
 
+impl Robot {
+    /// Position after `seconds`, teleporting around the edges of a `width` x `height` grid.
+    pub fn position_at(&self, seconds: usize, width: usize, height: usize) -> (usize, usize) {
+        let (x, y) = self.position;
+        let (vx, vy) = self.velocity;
+        (wrap(x, vx, seconds, width), wrap(y, vy, seconds, height))
+    }
+}
+
+#[inline]
+fn wrap(position: usize, velocity: i32, seconds: usize, bound: usize) -> usize {
+    // the position repeats every `bound` seconds, which keeps the product small
+    let seconds = (seconds % bound) as i64;
+    let bound = bound as i64;
+    (position as i64 + velocity as i64 * seconds).rem_euclid(bound) as usize
+}
+
 lazy_static! {
     static ref ROBOT_PATTERN: Regex = Regex::new(r"p=(\d+),(\d+) v=(-?\d+),(-?\d+)").unwrap();
 }
@@ -148,23 +268,86 @@ fn parse_number<T: FromStr<Err = ParseIntError>>(s: &str) -> Result<T, String> {
     s.parse::<T>().map_err(|e| e.to_string())
 }
 
-lazy_static! {
-    static ref CHRISTMAS_TREE: Vec<(usize, usize)> = read_christmas_tree();
-}
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
 
-fn read_christmas_tree() -> Vec<(usize, usize)> {
-    let content = read_file(Path::new(file!()), "tree.txt");
+    use crate::aoc_core::read_file;
 
-    content
-        .lines()
-        .enumerate()
-        .flat_map(|(y, row)| {
-            row.chars().enumerate().filter_map(move |(x, c)| {
-                if c == 'X' {
-                    return Some((x, y));
-                }
-                None
-            })
-        })
-        .collect::<Vec<_>>()
+    use super::*;
+
+    #[test]
+    fn test_position_at() -> Result<(), String> {
+        let robot = Robot::from_str("p=2,4 v=2,-3")?;
+
+        assert_eq!(robot.position_at(1, 11, 7), (4, 1));
+        assert_eq!(robot.position_at(5, 11, 7), (1, 3));
+        assert_eq!(robot.position_at(5 + period(11, 7) * 1000, 11, 7), (1, 3));
+        // usize::MAX seconds are 4 seconds modulo 11 and 1 second modulo 7
+        assert_eq!(robot.position_at(usize::MAX, 11, 7), (10, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_safety_factor_sample() -> Result<(), String> {
+        let robots = parse_robots(&read_file(Path::new(file!()), "sample.txt"))?;
+
+        assert_eq!(safety_factor(&robots, 100, 11, 7), 12);
+        assert_eq!(period(11, 7), 77);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_empty_grid() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        for part in [TaskPart::A, TaskPart::B] {
+            for grid in ["0x7", "11x0"] {
+                let overrides = [("grid".to_owned(), grid.to_owned())];
+                let params = Params::resolve(&Day14.params(&part), None, &overrides)?;
+                let result = match part {
+                    TaskPart::A => Day14.solve_a_with_params(contents.clone(), &params),
+                    TaskPart::B => Day14.solve_b_with_params(contents.clone(), &params),
+                };
+                assert!(result.is_err());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_zero_frame_scale() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        let overrides = [
+            ("frames".to_owned(), "0..1".to_owned()),
+            ("frame_scale".to_owned(), "0".to_owned()),
+        ];
+        let params = Params::resolve(&Day14.params(&TaskPart::A), None, &overrides)?;
+
+        assert!(Day14.solve_a_with_params(contents, &params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detects_drawn_picture() -> Result<(), String> {
+        // robots drift away from a small square they form at second 30
+        let (width, height) = (31, 37);
+        let mut robots = Vec::new();
+        for i in 0..200usize {
+            let (x, y) = (10 + i % 5, 10 + (i / 5) % 5);
+            let velocity = ((i * 7 % 13) as i32 - 6, (i * 11 % 17) as i32 - 8);
+            let rewind = |p: usize, v: i32, bound: usize| {
+                (p as i64 - v as i64 * 30).rem_euclid(bound as i64) as usize
+            };
+            robots.push(Robot {
+                position: (rewind(x, velocity.0, width), rewind(y, velocity.1, height)),
+                velocity,
+            });
+        }
+
+        let detection = detect_christmas_tree(&robots, width, height).ok_or("no robots")?;
+
+        assert_eq!(detection.seconds, 30);
+        assert!(detection.score < 0.1);
+        Ok(())
+    }
 }
@@ -1,199 +1,162 @@
-use std::str::FromStr;
+use std::{fs, path::PathBuf, str::FromStr, time::Duration};
 
-use crate::aoc_core::{AocResult, AocTask};
+use play::{play, PlayOptions};
+use visualize::{visualize, VisualizeOptions};
+use warehouse::{first_divergence, Warehouse, MAX_BOX_WIDTH};
 
-mod part_b;
+use crate::{
+    aoc_core::{AocResult, AocTask, TaskPart},
+    aoc_params::{ParamKind, ParamSpec, Params},
+};
+
+mod play;
+mod visualize;
+mod warehouse;
 
 pub struct Day15;
 
 impl AocTask for Day15 {
-    fn solve_a(&self, contents: String) -> AocResult {
-        let mut wh = Warehouse::from_str(&contents)?;
-
-        for movement in wh.movements.clone() {
-            wh.move_robot(&movement);
-        }
-
-        let mut result = 0;
-        for (y, row) in wh.map.iter().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                match cell {
-                    WarehouseContent::BOX => {
-                        result += 100 * (y + 1) + x + 1;
-                    }
-                    _ => (),
-                }
-            }
-        }
-
-        Ok(result as i64)
+    fn params(&self, part: &TaskPart) -> Vec<ParamSpec> {
+        let explode = match part {
+            TaskPart::A => "1",
+            TaskPart::B => "2",
+        };
+        vec![
+            // every cell of the map is widened by this factor before moving
+            ParamSpec::new("explode", ParamKind::UInt, explode),
+            // validates the warehouse after every movement
+            ParamSpec::new("check", ParamKind::Flag, "false"),
+            ParamSpec::new("visualize", ParamKind::Flag, "false"),
+            // milliseconds between two movements when visualizing
+            ParamSpec::new("frame_delay", ParamKind::UInt, "50"),
+            // drive the robot yourself instead of replaying the movements
+            ParamSpec::new("play", ParamKind::Flag, "false"),
+            // file the played movements are saved to, together with the map
+            ParamSpec::new("save_moves", ParamKind::Text, ""),
+            // file with the map and movements of another run, e.g. written by save_moves;
+            // reports the first movement after which the two runs look different
+            ParamSpec::new("reference", ParamKind::Text, ""),
+        ]
     }
 
-    fn solve_b(&self, contents: String) -> AocResult {
-        part_b::solve_b(contents)
+    fn solve_a_with_params(&self, contents: String, params: &Params) -> AocResult {
+        solve(&contents, params)
     }
-}
 
-#[derive(Clone, Copy)]
-enum Direction {
-    UP,
-    RIGHT,
-    DOWN,
-    LEFT,
-}
-impl Direction {
-    fn as_deltas(&self) -> (i8, i8) {
-        match self {
-            Self::UP => (-1, 0),
-            Self::RIGHT => (0, 1),
-            Self::DOWN => (1, 0),
-            Self::LEFT => (0, -1),
-        }
-    }
-}
-impl FromStr for Direction {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "^" => Self::UP,
-            ">" => Self::RIGHT,
-            "v" => Self::DOWN,
-            "<" => Self::LEFT,
-            _ => return Err(format!("invalid direction: {s}")),
-        })
+    fn solve_b_with_params(&self, contents: String, params: &Params) -> AocResult {
+        solve(&contents, params)
     }
 }
 
-struct Warehouse {
-    map: Vec<Vec<WarehouseContent>>,
-    dimensions: (usize, usize),
-    robot_position: (usize, usize),
-    movements: Vec<Direction>,
-}
-impl Warehouse {
-    fn move_robot(&mut self, direction: &Direction) {
-        let (y, x) = self.robot_position;
-        if let Some((ny, nx)) = self.next_position(direction) {
-            let content = &self.map[ny][nx];
-            match content {
-                WarehouseContent::WALL => {}
-                WarehouseContent::EMPTY => {
-                    self.map[ny][nx] = WarehouseContent::ROBOT;
-                    self.map[y][x] = WarehouseContent::EMPTY;
-                    self.robot_position = (ny, nx);
-                }
-                WarehouseContent::BOX => {
-                    let mut cursor = (ny, nx);
-                    let mut next;
-                    loop {
-                        next = Self::_next_position(cursor, self.dimensions, direction);
-                        match next {
-                            None => break,
-                            Some((cy, cx)) => {
-                                match self.map[cy][cx] {
-                                    WarehouseContent::WALL => break,
-                                    WarehouseContent::EMPTY => {
-                                        self.map[ny][nx] = WarehouseContent::ROBOT;
-                                        self.map[y][x] = WarehouseContent::EMPTY;
-                                        self.map[cy][cx] = WarehouseContent::BOX;
-                                        self.robot_position = (ny, nx);
-                                        break;
-                                    }
-                                    WarehouseContent::ROBOT => unreachable!(),
-                                    WarehouseContent::BOX => {}
-                                };
-                                cursor = (cy, cx);
-                            }
-                        }
-                    }
-                }
-                WarehouseContent::ROBOT => unreachable!(),
-            }
-        }
+fn solve(contents: &str, params: &Params) -> AocResult {
+    let factor = params.uint("explode")? as usize;
+    if factor == 0 {
+        return Err("explode factor has to be at least 1".to_owned());
     }
-
-    fn next_position(&self, direction: &Direction) -> Option<(usize, usize)> {
-        Self::_next_position(self.robot_position, self.dimensions, direction)
+    let warehouse = Warehouse::from_str(contents)?;
+    if warehouse.box_width().saturating_mul(factor) > MAX_BOX_WIDTH {
+        return Err(format!(
+            "explode factor {factor} makes boxes wider than {MAX_BOX_WIDTH} cells"
+        ));
+    }
+    if params.flag("play")? {
+        let save_moves = params.text("save_moves")?;
+        let options = PlayOptions {
+            factor,
+            save_moves: (!save_moves.is_empty()).then(|| PathBuf::from(save_moves)),
+        };
+        return Ok(play(contents, &options)? as i64);
     }
-    fn _next_position(
-        position: (usize, usize),
-        dimensions: (usize, usize),
-        direction: &Direction,
-    ) -> Option<(usize, usize)> {
-        let (y, x) = position;
-        let (dy, dx) = direction.as_deltas();
-        let ny = y.checked_add_signed(dy as isize)?;
-        let nx = x.checked_add_signed(dx as isize)?;
-        let (height, width) = dimensions;
-        if ny >= height || nx >= width {
-            return None;
+    let reference = params.text("reference")?;
+    if !reference.is_empty() {
+        let recorded = fs::read_to_string(reference).map_err(|e| format!("{reference}: {e}"))?;
+        match divergence(contents, &recorded, factor)? {
+            Some(i) => println!("diverges from {reference} after movement {i}"),
+            None => println!("agrees with {reference}"),
         }
-        Some((ny, nx))
     }
-}
-impl FromStr for Warehouse {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines();
-        lines.next();
-        let mut map = Vec::new();
-        let mut robot_position = None;
-        for (y, line) in (&mut lines).enumerate() {
-            let raw_content = &line[1..line.len() - 1];
-            let mut row = Vec::new();
-            let mut all_walls = true;
-            for (x, c) in raw_content.chars().enumerate() {
-                let content = WarehouseContent::from_str(&c.to_string())?;
-                match content {
-                    WarehouseContent::ROBOT => {
-                        robot_position = Some((y, x));
-                        all_walls = false;
-                    }
-                    WarehouseContent::WALL => {}
-                    _ => {
-                        all_walls = false;
-                    }
+    let mut wh = warehouse.explode(factor);
+
+    match visualize_options(params)? {
+        Some(options) => visualize(&mut wh, &options),
+        None => {
+            let check = params.flag("check")?;
+            for (i, movement) in wh.movements.clone().iter().enumerate() {
+                wh.move_robot(movement);
+                if check {
+                    wh.check_consistency()
+                        .map_err(|e| format!("after movement {i} ({}): {e}", movement.symbol()))?;
                 }
-                row.push(content);
-            }
-            if all_walls {
-                break;
             }
-            map.push(row);
         }
-        lines.next();
-        let movements = lines
-            .flat_map(|line| line.chars().map(|c| Direction::from_str(&c.to_string())))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let dimensions = (map.len(), map[0].len());
-        Ok(Warehouse {
-            map,
-            dimensions,
-            robot_position: robot_position.ok_or("Robot position not found".to_owned())?,
-            movements,
-        })
     }
+
+    Ok(wh.gps_sum() as i64)
 }
 
-enum WarehouseContent {
-    ROBOT,
-    BOX,
-    EMPTY,
-    WALL,
+/// First movement of `contents` after which it looks different from the run `recorded`,
+/// both starting from the same map.
+fn divergence(contents: &str, recorded: &str, factor: usize) -> Result<Option<usize>, String> {
+    let reference = Warehouse::from_str(recorded)?;
+    let candidate = Warehouse::from_str(contents)?;
+    if reference.to_string() != candidate.to_string() {
+        return Err("the reference run starts from a different map".to_owned());
+    }
+    let (mut reference, mut candidate) = (reference.explode(factor), candidate.explode(factor));
+    Ok(first_divergence(&mut reference, &mut candidate))
 }
-impl FromStr for WarehouseContent {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "@" => Self::ROBOT,
-            "O" => Self::BOX,
-            "." => Self::EMPTY,
-            "#" => Self::WALL,
-            _ => return Err(format!("invalid warehouse content: {s}")),
-        })
+
+fn visualize_options(params: &Params) -> Result<Option<VisualizeOptions>, String> {
+    if !params.flag("visualize")? {
+        return Ok(None);
+    }
+    Ok(Some(VisualizeOptions {
+        frame_delay: Duration::from_millis(params.uint("frame_delay")?),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::aoc_core::read_file;
+
+    use super::*;
+
+    #[test]
+    fn test_rejects_too_wide_boxes() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        let explode = |factor: &str| {
+            let overrides = [("explode".to_owned(), factor.to_owned())];
+            Params::resolve(&Day15.params(&TaskPart::A), None, &overrides)
+        };
+
+        assert_eq!(solve(&contents, &explode("1")?)?, 10092);
+        assert!(solve(&contents, &explode("256")?).is_ok());
+        assert!(solve(&contents, &explode("257")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_divergence_from_reference() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        assert_eq!(divergence(&contents, &contents, 2)?, None);
+
+        // a run with every movement mirrored is off from the first movement on
+        let mirrored = contents
+            .chars()
+            .map(|c| match c {
+                '<' => '>',
+                '>' => '<',
+                '^' => 'v',
+                'v' => '^',
+                c => c,
+            })
+            .collect::<String>();
+        assert_eq!(divergence(&contents, &mirrored, 2)?, Some(0));
+
+        let other_map = contents.replacen('@', ".", 1).replacen("..", "@.", 1);
+        assert!(divergence(&contents, &other_map, 1).is_err());
+        Ok(())
     }
 }
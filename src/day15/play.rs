@@ -0,0 +1,188 @@
+use std::{
+    fs,
+    io::{stdin, stdout, IsTerminal, Read, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use super::{
+    visualize::{RawMode, CLEAR_SCREEN, CURSOR_HOME, HIDE_CURSOR, RESET, ROBOT, SHOW_CURSOR},
+    warehouse::{Direction, Warehouse},
+};
+
+/// Number of movements per line when saving, like the puzzle's sample
+const MOVES_PER_LINE: usize = 70;
+
+pub(crate) struct PlayOptions {
+    /// Factor the played warehouse is exploded by
+    pub factor: usize,
+    pub save_moves: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Move(Direction),
+    Undo,
+    Save,
+    Quit,
+}
+
+/// Lets the user drive the robot with the arrow keys or `wasd`, starting from the map in
+/// `contents`. Every movement is also applied to the part A and part B warehouses, so their
+/// sums can be shown live. Returns the GPS sum of the played warehouse.
+pub(crate) fn play(contents: &str, options: &PlayOptions) -> Result<usize, String> {
+    let base = Warehouse::from_str(contents)?;
+    let mut part_a = base.explode(1);
+    let mut part_b = base.explode(2);
+    let mut board = base.explode(options.factor);
+
+    let raw_mode = stdin().is_terminal().then(RawMode::enable).flatten();
+    let mut bytes = stdin().lock().bytes().map_while(Result::ok);
+    let mut message = String::new();
+
+    print!("{HIDE_CURSOR}{CLEAR_SCREEN}");
+    loop {
+        draw(&board, &part_a, &part_b, &message, options);
+        let Some(action) = read_action(&mut bytes) else {
+            break;
+        };
+        message.clear();
+        match action {
+            Action::Move(direction) => {
+                part_a.move_robot(&direction);
+                part_b.move_robot(&direction);
+                if board.move_robot(&direction).robot == (0, 0) {
+                    message = "blocked".to_owned();
+                }
+            }
+            Action::Undo => {
+                part_a.undo();
+                part_b.undo();
+                if board.undo().is_none() {
+                    message = "nothing to undo".to_owned();
+                }
+            }
+            Action::Save => message = save(contents, &board, options)?,
+            Action::Quit => break,
+        }
+    }
+    print!("{SHOW_CURSOR}");
+    stdout().flush().unwrap();
+    drop(raw_mode);
+
+    if options.save_moves.is_some() {
+        println!("{}", save(contents, &board, options)?);
+    }
+    Ok(board.gps_sum())
+}
+
+fn draw(
+    board: &Warehouse,
+    part_a: &Warehouse,
+    part_b: &Warehouse,
+    message: &str,
+    options: &PlayOptions,
+) {
+    let mut frame = String::from(CURSOR_HOME);
+    for c in board.to_string().chars() {
+        match c {
+            '@' => frame += &format!("{ROBOT}{c}{RESET}"),
+            _ => frame.push(c),
+        }
+    }
+    frame += &format!(
+        "\x1b[2KGPS {} | part A {} | part B {} | {} moves\n",
+        board.gps_sum(),
+        part_a.gps_sum(),
+        part_b.gps_sum(),
+        board.history().len()
+    );
+    frame += "\x1b[2Karrows/wasd: move, u: undo, ";
+    if options.save_moves.is_some() {
+        frame += "f: save, ";
+    }
+    frame += &format!("q: quit\n\x1b[2K{message}\n");
+    print!("{frame}");
+    stdout().flush().unwrap();
+}
+
+/// Reads bytes until they form an action, `None` once the input is exhausted.
+fn read_action(bytes: &mut impl Iterator<Item = u8>) -> Option<Action> {
+    loop {
+        let action = match bytes.next()? {
+            b'w' => Action::Move(Direction::Up),
+            b'a' => Action::Move(Direction::Left),
+            b's' => Action::Move(Direction::Down),
+            b'd' => Action::Move(Direction::Right),
+            b'u' => Action::Undo,
+            b'f' => Action::Save,
+            b'q' => Action::Quit,
+            // arrow keys are `ESC [ A` to `ESC [ D`
+            0x1b => match (bytes.next()?, bytes.next()?) {
+                (b'[', b'A') => Action::Move(Direction::Up),
+                (b'[', b'B') => Action::Move(Direction::Down),
+                (b'[', b'C') => Action::Move(Direction::Right),
+                (b'[', b'D') => Action::Move(Direction::Left),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        return Some(action);
+    }
+}
+
+fn save(contents: &str, board: &Warehouse, options: &PlayOptions) -> Result<String, String> {
+    let Some(path) = &options.save_moves else {
+        return Ok("no file to save to, set save_moves".to_owned());
+    };
+    let moves = board
+        .history()
+        .iter()
+        .map(|m| m.direction.symbol())
+        .collect::<String>();
+    fs::write(path, recorded_input(contents, &moves))
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(format!("saved {} moves to {}", moves.len(), path.display()))
+}
+
+/// Puzzle input with the map of `contents` and the given movements.
+fn recorded_input(contents: &str, moves: &str) -> String {
+    let mut input = String::new();
+    for line in contents.lines().take_while(|line| !line.is_empty()) {
+        input += line;
+        input.push('\n');
+    }
+    input.push('\n');
+    for chunk in moves.as_bytes().chunks(MOVES_PER_LINE) {
+        input += std::str::from_utf8(chunk).unwrap();
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_action() {
+        let mut bytes = b"w\x1b[Dx\x1b[Bq".iter().copied();
+
+        assert_eq!(read_action(&mut bytes), Some(Action::Move(Direction::Up)));
+        assert_eq!(read_action(&mut bytes), Some(Action::Move(Direction::Left)));
+        assert_eq!(read_action(&mut bytes), Some(Action::Move(Direction::Down)));
+        assert_eq!(read_action(&mut bytes), Some(Action::Quit));
+        assert_eq!(read_action(&mut bytes), None);
+    }
+
+    #[test]
+    fn test_recorded_input_is_parsed_back() -> Result<(), String> {
+        let contents = "#####\n#...#\n#.O@#\n#####\n\n>>>\n";
+
+        let input = recorded_input(contents, &"<".repeat(75));
+
+        assert_eq!(input.lines().nth(5).map(str::len), Some(MOVES_PER_LINE));
+        assert_eq!(Warehouse::from_str(&input)?.movements.len(), 75);
+        Ok(())
+    }
+}
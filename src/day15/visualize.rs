@@ -0,0 +1,183 @@
+use std::{
+    collections::HashSet,
+    io::{stdin, stdout, IsTerminal, Read, Write},
+    process::{Command, Stdio},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use super::warehouse::Replay;
+
+pub(crate) const CLEAR_SCREEN: &str = "\x1b[2J";
+pub(crate) const CURSOR_HOME: &str = "\x1b[H";
+pub(crate) const HIDE_CURSOR: &str = "\x1b[?25l";
+pub(crate) const SHOW_CURSOR: &str = "\x1b[?25h";
+const MOVED_BOX: &str = "\x1b[1;30;43m";
+pub(crate) const ROBOT: &str = "\x1b[1;36m";
+pub(crate) const RESET: &str = "\x1b[0m";
+
+pub(crate) struct VisualizeOptions {
+    pub frame_delay: Duration,
+}
+
+enum Key {
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+    Quit,
+}
+
+/// Replays all movements in the terminal. Boxes that were pushed by the last movement are
+/// highlighted. While replaying, `space` pauses, `n` or `→` steps, `+`/`-` change the speed
+/// and `q` skips to the end.
+pub(crate) fn visualize<R: Replay>(warehouse: &mut R, options: &VisualizeOptions) {
+    let raw_mode = stdin().is_terminal().then(RawMode::enable).flatten();
+    let keys = raw_mode.as_ref().map(|_| read_keys());
+    let mut delay = options.frame_delay;
+    let mut paused = false;
+    let mut skip = false;
+
+    print!("{HIDE_CURSOR}{CLEAR_SCREEN}");
+    draw(
+        &warehouse.to_string(),
+        &HashSet::new(),
+        "start",
+        paused,
+        delay,
+    );
+    for index in 0..warehouse.n_movements() {
+        if !skip {
+            loop {
+                let key = match (&keys, paused) {
+                    (None, _) => {
+                        thread::sleep(delay);
+                        break;
+                    }
+                    (Some(keys), true) => keys.recv().ok(),
+                    (Some(keys), false) => match keys.recv_timeout(delay) {
+                        Ok(key) => Some(key),
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => None,
+                    },
+                };
+                match key {
+                    None => {
+                        paused = false;
+                        break;
+                    }
+                    Some(Key::Step) => break,
+                    Some(Key::TogglePause) => paused = !paused,
+                    Some(Key::Faster) => delay /= 2,
+                    Some(Key::Slower) => delay = (delay * 2).max(Duration::from_millis(1)),
+                    Some(Key::Quit) => {
+                        skip = true;
+                        break;
+                    }
+                }
+                if !paused {
+                    break;
+                }
+            }
+        }
+
+        let movement = warehouse.replay_movement(index);
+        if skip {
+            continue;
+        }
+        let pushed = warehouse.pushed_boxes().into_iter().collect();
+        let status = format!("{}/{} {movement}", index + 1, warehouse.n_movements());
+        draw(&warehouse.to_string(), &pushed, &status, paused, delay);
+    }
+    if skip {
+        let current = warehouse.to_string();
+        draw(
+            &current,
+            &HashSet::new(),
+            "skipped to the end",
+            false,
+            delay,
+        );
+    }
+    print!("{SHOW_CURSOR}");
+    stdout().flush().unwrap();
+    drop(raw_mode);
+}
+
+fn draw(
+    current: &str,
+    pushed: &HashSet<(usize, usize)>,
+    status: &str,
+    paused: bool,
+    delay: Duration,
+) {
+    let mut frame = String::from(CURSOR_HOME);
+    for (y, line) in current.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                _ if pushed.contains(&(y, x)) => frame += &format!("{MOVED_BOX}{c}{RESET}"),
+                '@' => frame += &format!("{ROBOT}{c}{RESET}"),
+                _ => frame.push(c),
+            }
+        }
+        frame.push('\n');
+    }
+    frame += &format!(
+        "\x1b[2K{status}{} | {}ms | space: pause, n: step, +/-: speed, q: skip\n",
+        if paused { " (paused)" } else { "" },
+        delay.as_millis()
+    );
+    print!("{frame}");
+    stdout().flush().unwrap();
+}
+
+/// Reads single key presses, requires the terminal to be in raw mode.
+fn read_keys() -> Receiver<Key> {
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let mut bytes = stdin().lock().bytes();
+        while let Some(Ok(byte)) = bytes.next() {
+            let key = match byte {
+                b' ' | b'p' => Key::TogglePause,
+                b'n' | b'.' => Key::Step,
+                b'+' => Key::Faster,
+                b'-' => Key::Slower,
+                b'q' => Key::Quit,
+                // arrow right is `ESC [ C`
+                0x1b => match (bytes.next(), bytes.next()) {
+                    (Some(Ok(b'[')), Some(Ok(b'C'))) => Key::Step,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Unbuffered terminal input without echo, restored when dropped.
+pub(crate) struct RawMode;
+
+impl RawMode {
+    pub(crate) fn enable() -> Option<RawMode> {
+        stty(&["-icanon", "-echo", "min", "1"]).then_some(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        stty(&["icanon", "echo"]);
+    }
+}
+
+fn stty(args: &[&str]) -> bool {
+    Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .status()
+        .is_ok_and(|s| s.success())
+}
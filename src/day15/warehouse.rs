@@ -0,0 +1,629 @@
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+impl Direction {
+    fn as_deltas(&self) -> (i8, i8) {
+        match self {
+            Self::Up => (-1, 0),
+            Self::Right => (0, 1),
+            Self::Down => (1, 0),
+            Self::Left => (0, -1),
+        }
+    }
+    fn opposite(&self) -> Direction {
+        match self {
+            Self::Up => Self::Down,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+        }
+    }
+    pub(crate) fn symbol(&self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
+}
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "^" => Self::Up,
+            ">" => Self::Right,
+            "v" => Self::Down,
+            "<" => Self::Left,
+            _ => return Err(format!("invalid direction: {s}")),
+        })
+    }
+}
+
+/// What a single movement changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Move {
+    pub(crate) direction: Direction,
+    /// `(dy, dx)` the robot moved by, `(0, 0)` if it was blocked
+    pub(crate) robot: (i8, i8),
+    /// Left edges of the shifted boxes before the movement
+    pub(crate) boxes: Vec<(usize, usize)>,
+}
+
+/// Warehouse without its outer walls. All boxes are `box_width` cells wide.
+pub(crate) struct Warehouse {
+    pub(crate) map: Vec<Vec<WarehouseContent>>,
+    dimensions: (usize, usize),
+    robot_position: (usize, usize),
+    pub(crate) movements: Vec<Direction>,
+    box_width: usize,
+    /// Rows and columns of outer walls that were stripped from the map
+    border: (usize, usize),
+    n_boxes: usize,
+    /// Movements done so far, most recent last
+    history: Vec<Move>,
+}
+impl Warehouse {
+    /// Moves the robot and records the movement, even if the robot was blocked.
+    pub(crate) fn move_robot(&mut self, direction: &Direction) -> &Move {
+        let pushed = self
+            .next_position(self.robot_position, direction)
+            .and_then(|target| Some((target, self.boxes_to_push(target, direction)?)));
+        let mut record = Move {
+            direction: *direction,
+            robot: (0, 0),
+            boxes: Vec::new(),
+        };
+        if let Some(((ny, nx), cells)) = pushed {
+            let mut moved = Vec::with_capacity(cells.len());
+            for (y, x) in cells {
+                if self.map[y][x] == WarehouseContent::Box(0) {
+                    record.boxes.push((y, x));
+                }
+                moved.push((direction_go_from(direction, (y, x)), self.map[y][x]));
+                self.map[y][x] = WarehouseContent::Empty;
+            }
+            for ((y, x), content) in moved {
+                self.map[y][x] = content;
+            }
+            let (y, x) = self.robot_position;
+            self.map[y][x] = WarehouseContent::Empty;
+            self.map[ny][nx] = WarehouseContent::Robot;
+            self.robot_position = (ny, nx);
+            record.robot = direction.as_deltas();
+        }
+        self.history.push(record);
+        self.history.last().unwrap()
+    }
+
+    pub(crate) fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Reverts the most recent movement, `None` if there is nothing left to undo.
+    pub(crate) fn undo(&mut self) -> Option<Move> {
+        let record = self.history.pop()?;
+        if record.robot == (0, 0) {
+            return Some(record);
+        }
+        let back = record.direction.opposite();
+        let (y, x) = self.robot_position;
+        self.map[y][x] = WarehouseContent::Empty;
+        for &(y, x) in &record.boxes {
+            let (ny, nx) = direction_go_from(&record.direction, (y, x));
+            for bx in nx..nx + self.box_width {
+                self.map[ny][bx] = WarehouseContent::Empty;
+            }
+        }
+        for &(y, x) in &record.boxes {
+            for (offset, bx) in (x..x + self.box_width).enumerate() {
+                self.map[y][bx] = WarehouseContent::Box(offset as u8);
+            }
+        }
+        let (y, x) = direction_go_from(&back, self.robot_position);
+        self.map[y][x] = WarehouseContent::Robot;
+        self.robot_position = (y, x);
+        Some(record)
+    }
+
+    /// Checks that no box got lost and that every box is still in one piece.
+    pub(crate) fn check_consistency(&self) -> Result<(), String> {
+        let mut n_boxes = 0;
+        let mut n_robots = 0;
+        for (y, row) in self.map.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                match *cell {
+                    WarehouseContent::Robot => {
+                        n_robots += 1;
+                        if (y, x) != self.robot_position {
+                            return Err(format!(
+                                "robot at {:?}, expected at {:?}",
+                                (y, x),
+                                self.robot_position
+                            ));
+                        }
+                    }
+                    WarehouseContent::Box(offset) => {
+                        let offset = offset as usize;
+                        if offset == 0 {
+                            n_boxes += 1;
+                        }
+                        let previous = offset
+                            .checked_sub(1)
+                            .map(|o| (x.checked_sub(1), WarehouseContent::Box(o as u8)));
+                        let next = (offset + 1 < self.box_width)
+                            .then(|| (Some(x + 1), WarehouseContent::Box(offset as u8 + 1)));
+                        for (neighbour, expected) in previous.into_iter().chain(next) {
+                            if neighbour.and_then(|nx| row.get(nx)) != Some(&expected) {
+                                return Err(format!("box at {:?} is torn apart", (y, x)));
+                            }
+                        }
+                        if offset >= self.box_width {
+                            return Err(format!("box at {:?} is too wide", (y, x)));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if n_robots != 1 {
+            return Err(format!("found {n_robots} robots"));
+        }
+        if n_boxes != self.n_boxes {
+            return Err(format!("found {n_boxes} boxes, expected {}", self.n_boxes));
+        }
+        Ok(())
+    }
+
+    /// All box cells that have to move for the robot to step onto `target`, `None` if a wall
+    /// is in the way. Boxes are pushed as a whole, so a box reached at one of its cells
+    /// pushes on with all of its cells.
+    fn boxes_to_push(
+        &self,
+        target: (usize, usize),
+        direction: &Direction,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut boxes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut frontier = vec![target];
+        while let Some((y, x)) = frontier.pop() {
+            match self.map[y][x] {
+                WarehouseContent::Wall => return None,
+                WarehouseContent::Empty | WarehouseContent::Robot => continue,
+                WarehouseContent::Box(offset) => {
+                    let left = x - offset as usize;
+                    for bx in left..left + self.box_width {
+                        if !seen.insert((y, bx)) {
+                            continue;
+                        }
+                        boxes.push((y, bx));
+                        frontier.push(self.next_position((y, bx), direction)?);
+                    }
+                }
+            }
+        }
+        Some(boxes)
+    }
+
+    fn next_position(
+        &self,
+        position: (usize, usize),
+        direction: &Direction,
+    ) -> Option<(usize, usize)> {
+        let (ny, nx) = direction_go_from(direction, position);
+        let (height, width) = self.dimensions;
+        if ny >= height || nx >= width {
+            return None;
+        }
+        Some((ny, nx))
+    }
+
+    /// Sum of the GPS coordinates of all boxes, measured at their left edge.
+    pub(crate) fn gps_sum(&self) -> usize {
+        let (border_y, border_x) = self.border;
+        let mut result = 0;
+        for (y, row) in self.map.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell == WarehouseContent::Box(0) {
+                    result += 100 * (y + border_y) + x + border_x;
+                }
+            }
+        }
+        result
+    }
+
+    /// Number of cells every box covers
+    pub(crate) fn box_width(&self) -> usize {
+        self.box_width
+    }
+
+    /// Widens every cell by `factor`, so boxes become `factor` times as wide. The robot
+    /// stays on the left of its widened cell.
+    pub(crate) fn explode(&self, factor: usize) -> Self {
+        let mut map = Vec::with_capacity(self.map.len());
+        let mut robot_position = self.robot_position;
+        for (y, row) in self.map.iter().enumerate() {
+            let mut exploded = Vec::with_capacity(row.len() * factor);
+            for (x, cell) in row.iter().enumerate() {
+                for k in 0..factor {
+                    exploded.push(match *cell {
+                        WarehouseContent::Box(offset) => {
+                            WarehouseContent::Box((offset as usize * factor + k) as u8)
+                        }
+                        WarehouseContent::Robot if k > 0 => WarehouseContent::Empty,
+                        WarehouseContent::Robot => {
+                            robot_position = (y, x * factor);
+                            WarehouseContent::Robot
+                        }
+                        content => content,
+                    });
+                }
+            }
+            map.push(exploded);
+        }
+
+        let (height, width) = self.dimensions;
+        let (border_y, border_x) = self.border;
+        Warehouse {
+            map,
+            dimensions: (height, width * factor),
+            robot_position,
+            movements: self.movements.clone(),
+            box_width: self.box_width * factor,
+            border: (border_y, border_x * factor),
+            n_boxes: self.n_boxes,
+            history: Vec::new(),
+        }
+    }
+}
+
+fn direction_go_from(direction: &Direction, (y, x): (usize, usize)) -> (usize, usize) {
+    let (dy, dx) = direction.as_deltas();
+    (
+        y.wrapping_add_signed(dy as isize),
+        x.wrapping_add_signed(dx as isize),
+    )
+}
+
+impl FromStr for Warehouse {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        lines.next();
+        let mut map = Vec::new();
+        let mut robot_position = None;
+        let mut box_width = 1;
+        for (y, line) in (&mut lines).enumerate() {
+            let raw_content = &line[1..line.len() - 1];
+            let mut row = Vec::new();
+            let mut all_walls = true;
+            for (x, c) in raw_content.chars().enumerate() {
+                let content = match c {
+                    '[' | ']' => {
+                        box_width = 2;
+                        WarehouseContent::Box((c == ']') as u8)
+                    }
+                    _ => WarehouseContent::from_str(&c.to_string())?,
+                };
+                match content {
+                    WarehouseContent::Robot => {
+                        robot_position = Some((y, x));
+                        all_walls = false;
+                    }
+                    WarehouseContent::Wall => {}
+                    _ => {
+                        all_walls = false;
+                    }
+                }
+                row.push(content);
+            }
+            if all_walls {
+                break;
+            }
+            map.push(row);
+        }
+        lines.next();
+        let movements = lines
+            .flat_map(|line| line.chars().map(|c| Direction::from_str(&c.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let dimensions = (map.len(), map.first().ok_or("Empty warehouse")?.len());
+        let n_boxes = map
+            .iter()
+            .flatten()
+            .filter(|&&c| c == WarehouseContent::Box(0))
+            .count();
+        Ok(Warehouse {
+            map,
+            dimensions,
+            robot_position: robot_position.ok_or("Robot position not found".to_owned())?,
+            movements,
+            box_width,
+            border: (1, 1),
+            n_boxes,
+            history: Vec::new(),
+        })
+    }
+}
+
+impl Display for Warehouse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.map {
+            for cell in row {
+                let symbol = match cell {
+                    WarehouseContent::Box(_) if self.box_width == 1 => 'O',
+                    WarehouseContent::Box(0) => '[',
+                    WarehouseContent::Box(offset) if *offset as usize == self.box_width - 1 => ']',
+                    WarehouseContent::Box(_) => '=',
+                    WarehouseContent::Robot => '@',
+                    WarehouseContent::Wall => '#',
+                    WarehouseContent::Empty => '.',
+                };
+                f.write_fmt(format_args!("{}", symbol))?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// A warehouse whose robot movements can be replayed one at a time.
+pub(crate) trait Replay: Display {
+    fn n_movements(&self) -> usize;
+    /// Applies the movement at `index` and returns its symbol.
+    fn replay_movement(&mut self, index: usize) -> char;
+    /// Reverts the most recently replayed movement, `false` if there is none.
+    fn undo_movement(&mut self) -> bool;
+    /// Cells covered by the boxes the most recently replayed movement pushed, in the
+    /// coordinates of the displayed rows.
+    fn pushed_boxes(&self) -> Vec<(usize, usize)>;
+}
+
+impl Replay for Warehouse {
+    fn n_movements(&self) -> usize {
+        self.movements.len()
+    }
+
+    fn replay_movement(&mut self, index: usize) -> char {
+        let movement = self.movements[index];
+        self.move_robot(&movement);
+        movement.symbol()
+    }
+
+    fn undo_movement(&mut self) -> bool {
+        self.undo().is_some()
+    }
+
+    fn pushed_boxes(&self) -> Vec<(usize, usize)> {
+        let Some(record) = self.history.last() else {
+            return Vec::new();
+        };
+        record
+            .boxes
+            .iter()
+            .flat_map(|&left_edge| {
+                let (y, x) = direction_go_from(&record.direction, left_edge);
+                (x..x + self.box_width).map(move |bx| (y, bx))
+            })
+            .collect()
+    }
+}
+
+/// Index of the first movement after which `candidate` looks different from `reference`,
+/// found by bisecting over the movements. Both have to start out in the same state and a
+/// run is assumed to stay off once it diverged.
+pub(crate) fn first_divergence<A: Replay, B: Replay>(
+    reference: &mut A,
+    candidate: &mut B,
+) -> Option<usize> {
+    let n = reference.n_movements().min(candidate.n_movements());
+    let mut applied = 0;
+    let mut seek = |reference: &mut A, candidate: &mut B, target: usize| {
+        while applied < target {
+            reference.replay_movement(applied);
+            candidate.replay_movement(applied);
+            applied += 1;
+        }
+        while applied > target {
+            reference.undo_movement();
+            candidate.undo_movement();
+            applied -= 1;
+        }
+        reference.to_string() == candidate.to_string()
+    };
+
+    if seek(reference, candidate, n) {
+        return None;
+    }
+    // states agree after `low` movements and differ after `high`
+    let (mut low, mut high) = (0, n);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        if seek(reference, candidate, mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some(high - 1)
+}
+
+/// Box cells store their offset from the left edge in a `u8`.
+pub(crate) const MAX_BOX_WIDTH: usize = u8::MAX as usize + 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum WarehouseContent {
+    Robot,
+    Empty,
+    Wall,
+    /// Cell of a box, counted from its left edge
+    Box(u8),
+}
+impl FromStr for WarehouseContent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "@" => Self::Robot,
+            "O" => Self::Box(0),
+            "." => Self::Empty,
+            "#" => Self::Wall,
+            _ => return Err(format!("invalid warehouse content: {s}")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::aoc_core::read_file;
+
+    use super::*;
+
+    fn run(name: &str, factor: usize) -> Result<Warehouse, String> {
+        let mut wh = Warehouse::from_str(&read_file(Path::new(file!()), name))?.explode(factor);
+        for movement in wh.movements.clone() {
+            wh.move_robot(&movement);
+        }
+        Ok(wh)
+    }
+
+    #[test]
+    fn test_narrow_boxes() -> Result<(), String> {
+        assert_eq!(run("simple.txt", 1)?.gps_sum(), 2028);
+        assert_eq!(run("sample.txt", 1)?.gps_sum(), 10092);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_boxes() -> Result<(), String> {
+        assert_eq!(run("simple_b.txt", 2)?.gps_sum(), 618);
+        assert_eq!(run("sample.txt", 2)?.gps_sum(), 9021);
+        Ok(())
+    }
+
+    #[test]
+    fn test_triple_width_pushes_whole_box() -> Result<(), String> {
+        let mut wh = Warehouse::from_str("#####\n#...#\n#.O.#\n#.@.#\n#####\n\n^")?.explode(3);
+        assert_eq!(wh.to_string(), ".........\n...[=]...\n...@.....\n");
+
+        wh.move_robot(&Direction::Up);
+
+        assert_eq!(wh.to_string(), "...[=]...\n...@.....\n.........\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_restores_every_state() -> Result<(), String> {
+        let mut wh = Warehouse::from_str(&read_file(Path::new(file!()), "sample.txt"))?.explode(2);
+        let mut states = vec![wh.to_string()];
+        for movement in wh.movements.clone() {
+            wh.move_robot(&movement);
+            wh.check_consistency()?;
+            states.push(wh.to_string());
+        }
+
+        while let Some(record) = wh.undo() {
+            states.pop();
+            assert_eq!(Some(&wh.to_string()), states.last(), "undo {record:?}");
+        }
+        assert_eq!(states.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_record() -> Result<(), String> {
+        let mut wh = Warehouse::from_str("#####\n#...#\n#.O.#\n#.@.#\n#####\n\n^")?.explode(2);
+
+        let record = wh.move_robot(&Direction::Up).clone();
+
+        assert_eq!(record.robot, (-1, 0));
+        assert_eq!(record.boxes, vec![(1, 2)]);
+        assert_eq!(wh.move_robot(&Direction::Up).robot, (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_consistency_finds_torn_box() -> Result<(), String> {
+        let mut wh = Warehouse::from_str("#####\n#...#\n#.O.#\n#.@.#\n#####\n\n^")?.explode(2);
+        wh.check_consistency()?;
+
+        wh.map[1][3] = WarehouseContent::Empty;
+
+        assert!(wh.check_consistency().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pushed_boxes() -> Result<(), String> {
+        let mut wh = Warehouse::from_str("#######\n#@OOO.#\n#######\n\n>>")?;
+
+        wh.replay_movement(0);
+        let mut pushed = wh.pushed_boxes();
+        pushed.sort();
+        assert_eq!(pushed, vec![(0, 2), (0, 3), (0, 4)]);
+
+        wh.replay_movement(1);
+        assert_eq!(wh.pushed_boxes(), Vec::new());
+        Ok(())
+    }
+
+    /// Replays the movement at `flipped` in the opposite direction.
+    struct Flipped {
+        warehouse: Warehouse,
+        flipped: usize,
+    }
+    impl Display for Flipped {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.warehouse.fmt(f)
+        }
+    }
+    impl Replay for Flipped {
+        fn n_movements(&self) -> usize {
+            self.warehouse.n_movements()
+        }
+
+        fn replay_movement(&mut self, index: usize) -> char {
+            let mut movement = self.warehouse.movements[index];
+            if index == self.flipped {
+                movement = movement.opposite();
+            }
+            self.warehouse.move_robot(&movement);
+            movement.symbol()
+        }
+
+        fn undo_movement(&mut self) -> bool {
+            self.warehouse.undo_movement()
+        }
+
+        fn pushed_boxes(&self) -> Vec<(usize, usize)> {
+            self.warehouse.pushed_boxes()
+        }
+    }
+
+    #[test]
+    fn test_first_divergence() -> Result<(), String> {
+        let contents = read_file(Path::new(file!()), "sample.txt");
+        let mut reference = Warehouse::from_str(&contents)?.explode(2);
+        let mut candidate = Flipped {
+            warehouse: Warehouse::from_str(&contents)?.explode(2),
+            flipped: 321,
+        };
+
+        assert_eq!(first_divergence(&mut reference, &mut candidate), Some(321));
+
+        let mut reference = Warehouse::from_str(&contents)?;
+        let mut candidate = Warehouse::from_str(&contents)?;
+        assert_eq!(first_divergence(&mut reference, &mut candidate), None);
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use aoc_core::run;
 
 mod aoc_core;
+mod aoc_params;
+mod aoc_util;
 use aoc_register::aoc_register;
 
 use crate::aoc_core::AocTask;